@@ -1,27 +1,32 @@
-use std::thread;
+#[cfg(windows)]
 use std::time::Duration;
-use vmulti_client::keyboard::{Keyboard, KeyboardKey, KeyboardModifierKey, KeysClick};
-
+#[cfg(windows)]
+use vmulti_client::keyboard::{Keyboard, KeyboardModifierKey, KeysClick};
+#[cfg(windows)]
+use vmulti_client::layout::KeyboardLayout;
+#[cfg(windows)]
+use vmulti_client::typing::TypingOptions;
+
+#[cfg(windows)]
 fn main() {
-    let keyboard = Keyboard::init().unwrap();
-
-    keyboard.send_click(KeysClick::empty().add_modifier(KeyboardModifierKey::RightWindows));
-    keyboard.send_click(KeysClick::empty());
-
-    let sleep_duration = Duration::from_millis(100);
-    thread::sleep(sleep_duration);
+    let keyboard = Keyboard::init()
+        .unwrap()
+        .set_hold_duration(Duration::from_millis(20));
 
-    keyboard.send_click(
-        KeysClick::new(KeyboardKey::H).add_modifier(KeyboardModifierKey::RightShift),
-    );
+    keyboard
+        .tap(KeysClick::empty().add_modifier(KeyboardModifierKey::RightWindows))
+        .unwrap();
 
-    keyboard.send_click(KeysClick::new(KeyboardKey::E));
+    let options = TypingOptions::new()
+        .set_delay(Duration::from_millis(100))
+        .set_jitter(Duration::from_millis(30));
 
-    keyboard.send_click(KeysClick::new(KeyboardKey::L));
-    keyboard.send_click(KeysClick::empty());
-
-    keyboard.send_click(KeysClick::new(KeyboardKey::L));
+    keyboard
+        .type_text(&KeyboardLayout::us(), "Hello", &options)
+        .unwrap();
+}
 
-    keyboard.send_click(KeysClick::new(KeyboardKey::O));
-    keyboard.send_click(KeysClick::empty());
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("The vmulti driver only runs on Windows.");
 }
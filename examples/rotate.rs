@@ -1,8 +1,13 @@
+#[cfg(windows)]
 use std::thread;
+#[cfg(windows)]
 use std::time::Duration;
+#[cfg(windows)]
 use vmulti_client::display::get_displays_info;
+#[cfg(windows)]
 use vmulti_client::mouse::{Mouse, MouseButton, MouseClick};
 
+#[cfg(windows)]
 fn main() {
     let displays_info = get_displays_info();
     let display_info = displays_info[1];
@@ -42,3 +47,8 @@ fn main() {
             .set_position(center_x, center_y),
     );
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("The vmulti driver only runs on Windows.");
+}
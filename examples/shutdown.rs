@@ -1,8 +1,13 @@
+#[cfg(windows)]
 use std::thread;
+#[cfg(windows)]
 use std::time::Duration;
+#[cfg(windows)]
 use vmulti_client::display::get_displays_info;
+#[cfg(windows)]
 use vmulti_client::mouse::{Mouse, MouseButton, MouseClick};
 
+#[cfg(windows)]
 fn main() {
     let displays_info = get_displays_info();
     let mouse = Mouse::init(displays_info).unwrap();
@@ -30,3 +35,8 @@ fn main() {
     thread::sleep(sleep_duration);
     mouse.send_click(MouseClick::new().set_position(20, 960));
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("The vmulti driver only runs on Windows.");
+}
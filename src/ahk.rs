@@ -0,0 +1,479 @@
+use crate::keyboard::{
+    closest_name, KeyboardKey, KeyboardModifierKey, KeysClick, Modifiers, ParseKeyError,
+    ParseKeyErrorReason,
+};
+use crate::layout::KeyboardLayout;
+use crate::sequence::KeySequence;
+
+const fn key(key: KeyboardKey) -> KeysClick {
+    KeysClick::new(key)
+}
+
+const fn modifier(modifier: KeyboardModifierKey) -> KeysClick {
+    KeysClick::empty().add_modifier(modifier)
+}
+
+// Key names supported between braces, compared ignoring case.
+const AHK_KEYS: &[(&str, KeysClick)] = &[
+    ("Enter", key(KeyboardKey::Enter)),
+    ("Escape", key(KeyboardKey::Esc)),
+    ("Esc", key(KeyboardKey::Esc)),
+    ("Space", key(KeyboardKey::Space)),
+    ("Tab", key(KeyboardKey::Tab)),
+    ("Backspace", key(KeyboardKey::Del)),
+    ("BS", key(KeyboardKey::Del)),
+    ("Delete", key(KeyboardKey::DeleteForward)),
+    ("Del", key(KeyboardKey::DeleteForward)),
+    ("Insert", key(KeyboardKey::Insert)),
+    ("Ins", key(KeyboardKey::Insert)),
+    ("Home", key(KeyboardKey::Home)),
+    ("End", key(KeyboardKey::End)),
+    ("PgUp", key(KeyboardKey::PageUp)),
+    ("PgDn", key(KeyboardKey::PageDown)),
+    ("Up", key(KeyboardKey::UpArrow)),
+    ("Down", key(KeyboardKey::DownArrow)),
+    ("Left", key(KeyboardKey::LeftArrow)),
+    ("Right", key(KeyboardKey::RightArrow)),
+    ("ScrollLock", key(KeyboardKey::ScrollLock)),
+    ("CapsLock", key(KeyboardKey::CapsLock)),
+    ("NumLock", key(KeyboardKey::NumLock)),
+    ("PrintScreen", key(KeyboardKey::PrintScreen)),
+    ("Pause", key(KeyboardKey::Pause)),
+    ("AppsKey", key(KeyboardKey::Application)),
+    ("F1", key(KeyboardKey::F1)),
+    ("F2", key(KeyboardKey::F2)),
+    ("F3", key(KeyboardKey::F3)),
+    ("F4", key(KeyboardKey::F4)),
+    ("F5", key(KeyboardKey::F5)),
+    ("F6", key(KeyboardKey::F6)),
+    ("F7", key(KeyboardKey::F7)),
+    ("F8", key(KeyboardKey::F8)),
+    ("F9", key(KeyboardKey::F9)),
+    ("F10", key(KeyboardKey::F10)),
+    ("F11", key(KeyboardKey::F11)),
+    ("F12", key(KeyboardKey::F12)),
+    ("Numpad0", key(KeyboardKey::Keypad0)),
+    ("Numpad1", key(KeyboardKey::Keypad1)),
+    ("Numpad2", key(KeyboardKey::Keypad2)),
+    ("Numpad3", key(KeyboardKey::Keypad3)),
+    ("Numpad4", key(KeyboardKey::Keypad4)),
+    ("Numpad5", key(KeyboardKey::Keypad5)),
+    ("Numpad6", key(KeyboardKey::Keypad6)),
+    ("Numpad7", key(KeyboardKey::Keypad7)),
+    ("Numpad8", key(KeyboardKey::Keypad8)),
+    ("Numpad9", key(KeyboardKey::Keypad9)),
+    ("NumpadDot", key(KeyboardKey::KeypadPeriod)),
+    ("NumpadDiv", key(KeyboardKey::KeypadSlash)),
+    ("NumpadMult", key(KeyboardKey::KeypadAsterisk)),
+    ("NumpadAdd", key(KeyboardKey::KeypadPlus)),
+    ("NumpadSub", key(KeyboardKey::KeypadMinus)),
+    ("NumpadEnter", key(KeyboardKey::KeypadEnter)),
+    ("Volume_Mute", key(KeyboardKey::Mute)),
+    ("Volume_Up", key(KeyboardKey::VolumeUp)),
+    ("Volume_Down", key(KeyboardKey::VolumeDown)),
+    ("LWin", modifier(KeyboardModifierKey::LeftWindows)),
+    ("RWin", modifier(KeyboardModifierKey::RightWindows)),
+    ("Ctrl", modifier(KeyboardModifierKey::LeftControl)),
+    ("Control", modifier(KeyboardModifierKey::LeftControl)),
+    ("LCtrl", modifier(KeyboardModifierKey::LeftControl)),
+    ("LControl", modifier(KeyboardModifierKey::LeftControl)),
+    ("RCtrl", modifier(KeyboardModifierKey::RightControl)),
+    ("RControl", modifier(KeyboardModifierKey::RightControl)),
+    ("Shift", modifier(KeyboardModifierKey::LeftShift)),
+    ("LShift", modifier(KeyboardModifierKey::LeftShift)),
+    ("RShift", modifier(KeyboardModifierKey::RightShift)),
+    ("Alt", modifier(KeyboardModifierKey::LeftAlt)),
+    ("LAlt", modifier(KeyboardModifierKey::LeftAlt)),
+    ("RAlt", modifier(KeyboardModifierKey::RightAlt)),
+];
+
+// Brace commands of AutoHotkey that do not press keyboard keys, or change how the rest of the
+// string is sent.
+const UNSUPPORTED_COMMANDS: [&str; 14] = [
+    "Raw",
+    "Text",
+    "Blind",
+    "Click",
+    "ASC",
+    "LButton",
+    "RButton",
+    "MButton",
+    "XButton1",
+    "XButton2",
+    "WheelUp",
+    "WheelDown",
+    "WheelLeft",
+    "WheelRight",
+];
+
+/// Parses an AutoHotkey `Send` string, such as "^c", "+{Tab}", "{Enter 3}" or "{Shift down}",
+/// into a key sequence to send with `Keyboard::send_sequence`.
+///
+/// The `^`, `+`, `!` and `#` prefixes hold the left control, shift, alt and windows keys for
+/// the next key. Other characters are typed with the keystrokes of the layout, "{{}" and "{}}"
+/// standing for the braces themselves.
+pub fn parse_send(input: &str, layout: &KeyboardLayout) -> Result<KeySequence, ParseKeyError> {
+    let mut sequence = KeySequence::new();
+    let mut modifiers = Modifiers::empty();
+    let mut modifiers_start = None;
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let prefix = match c {
+            '^' => Some(Modifiers::LEFT_CTRL),
+            '+' => Some(Modifiers::LEFT_SHIFT),
+            '!' => Some(Modifiers::LEFT_ALT),
+            '#' => Some(Modifiers::LEFT_WIN),
+            _ => None,
+        };
+
+        if let Some(prefix) = prefix {
+            modifiers |= prefix;
+            modifiers_start.get_or_insert(start);
+            continue;
+        }
+
+        sequence = match c {
+            '{' => {
+                // The first character of the name may be a closing brace, as in "{}}".
+                let name_start = start + 1;
+                let first_len = chars.peek().map_or(0, |(_, c)| c.len_utf8());
+
+                let end = input[name_start + first_len..]
+                    .find('}')
+                    .map(|end| name_start + first_len + end)
+                    .ok_or_else(|| {
+                        let reason = ParseKeyErrorReason::MissingClosingBrace;
+                        ParseKeyError::new(&input[start..], start, reason)
+                    })?;
+
+                while chars.next_if(|(index, _)| *index <= end).is_some() {}
+
+                parse_braces(
+                    sequence,
+                    &input[name_start..end],
+                    name_start,
+                    modifiers,
+                    layout,
+                )?
+            }
+            '}' => {
+                let reason = ParseKeyErrorReason::UnexpectedBrace;
+                return Err(ParseKeyError::new("}", start, reason));
+            }
+            c => {
+                let keys_clicks = character_keys(c, start, layout)?;
+                add_keys_clicks(sequence, &keys_clicks, modifiers, 1)
+            }
+        };
+
+        modifiers = Modifiers::empty();
+        modifiers_start = None;
+    }
+
+    match modifiers_start {
+        Some(start) => {
+            let reason = ParseKeyErrorReason::MissingKeys;
+            Err(ParseKeyError::new(&input[start..], start, reason))
+        }
+        None => Ok(sequence),
+    }
+}
+
+// Adds the actions of the inside of braces, a key name or character followed by an optional
+// repeat count or "down" or "up".
+fn parse_braces(
+    sequence: KeySequence,
+    inside: &str,
+    start: usize,
+    modifiers: Modifiers,
+    layout: &KeyboardLayout,
+) -> Result<KeySequence, ParseKeyError> {
+    // A single character may be a space, which is not a separator then.
+    let (name, argument) = match inside.chars().count() {
+        1 => (inside, None),
+        _ => match inside.find(char::is_whitespace) {
+            Some(index) => (&inside[..index], Some(&inside[index..])),
+            None => (inside, None),
+        },
+    };
+
+    if name.is_empty() {
+        let reason = ParseKeyErrorReason::EmptySegment;
+        return Err(ParseKeyError::new(inside, start, reason));
+    }
+
+    let keys_clicks = match find_key(name) {
+        Some(keys_click) => vec![keys_click],
+        None if is_unsupported(name) => {
+            let reason = ParseKeyErrorReason::UnsupportedCommand;
+            return Err(ParseKeyError::new(name, start, reason));
+        }
+        None if name.chars().count() == 1 => {
+            character_keys(name.chars().next().unwrap(), start, layout)?
+        }
+        None => {
+            let names = AHK_KEYS.iter().map(|(name, _)| *name);
+            let error = ParseKeyError::new(name, start, ParseKeyErrorReason::UnknownKey);
+
+            return Err(error.with_suggestion(closest_name(name, names)));
+        }
+    };
+
+    let (argument, argument_start) = match argument {
+        Some(argument) => {
+            let leading = argument.len() - argument.trim_start().len();
+            (argument.trim(), start + name.len() + leading)
+        }
+        None => return Ok(add_keys_clicks(sequence, &keys_clicks, modifiers, 1)),
+    };
+
+    if let Ok(count) = argument.parse::<u32>() {
+        return Ok(add_keys_clicks(sequence, &keys_clicks, modifiers, count));
+    }
+
+    let keys_click = match keys_clicks.as_slice() {
+        [keys_click] => keys_click.add_modifiers(modifiers),
+        _ => {
+            let reason = ParseKeyErrorReason::UnsupportedCommand;
+            return Err(ParseKeyError::new(inside, start, reason));
+        }
+    };
+
+    if argument.eq_ignore_ascii_case("down") {
+        Ok(sequence.add_press(keys_click))
+    } else if argument.eq_ignore_ascii_case("up") {
+        Ok(sequence.add_release(keys_click))
+    } else if argument.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        let reason = ParseKeyErrorReason::InvalidRepeatCount;
+        Err(ParseKeyError::new(argument, argument_start, reason))
+    } else {
+        let reason = ParseKeyErrorReason::UnsupportedCommand;
+        Err(ParseKeyError::new(argument, argument_start, reason))
+    }
+}
+
+fn find_key(name: &str) -> Option<KeysClick> {
+    AHK_KEYS
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, keys_click)| *keys_click)
+}
+
+// Commands, unicode characters such as "U+20AC" and virtual keys or scan codes such as "vk41"
+// or "sc01E".
+fn is_unsupported(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let code = name.strip_prefix("vk").or_else(|| name.strip_prefix("sc"));
+
+    UNSUPPORTED_COMMANDS
+        .iter()
+        .any(|command| command.eq_ignore_ascii_case(&name))
+        || name.starts_with("u+")
+        || matches!(code, Some(code)
+            if !code.is_empty() && code.chars().all(|c| c.is_ascii_hexdigit() || c == 's'))
+}
+
+// Keystrokes typing the character, line breaks and tabs pressing their keys on any layout.
+fn character_keys(
+    character: char,
+    start: usize,
+    layout: &KeyboardLayout,
+) -> Result<Vec<KeysClick>, ParseKeyError> {
+    if let Some(keys_clicks) = layout.keys_for(character) {
+        return Ok(keys_clicks.to_vec());
+    }
+
+    match character {
+        '\n' | '\r' => Ok(vec![KeysClick::new(KeyboardKey::Enter)]),
+        '\t' => Ok(vec![KeysClick::new(KeyboardKey::Tab)]),
+        _ => {
+            let mut buffer = [0; 4];
+            let token = character.encode_utf8(&mut buffer);
+            let reason = ParseKeyErrorReason::UnmappedCharacter;
+
+            Err(ParseKeyError::new(token, start, reason))
+        }
+    }
+}
+
+fn add_keys_clicks(
+    mut sequence: KeySequence,
+    keys_clicks: &[KeysClick],
+    modifiers: Modifiers,
+    count: u32,
+) -> KeySequence {
+    if let [keys_click] = keys_clicks {
+        let keys_click = keys_click.add_modifiers(modifiers);
+
+        return match count {
+            0 => sequence,
+            1 => sequence.add_click(keys_click),
+            _ => sequence.add_repeat(keys_click, count),
+        };
+    }
+
+    for _ in 0..count {
+        for keys_click in keys_clicks {
+            sequence = sequence.add_click(keys_click.add_modifiers(modifiers));
+        }
+    }
+
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ahk::parse_send;
+    use crate::device::testing::RecordingTransport;
+    use crate::keyboard::{
+        Keyboard, KeyboardKey, KeyboardModifierKey, KeysClick, ParseKeyErrorReason,
+    };
+    use crate::layout::KeyboardLayout;
+    use crate::sequence::KeySequence;
+    use std::ops::Range;
+
+    fn parse(input: &str) -> KeySequence {
+        parse_send(input, &KeyboardLayout::us()).unwrap()
+    }
+
+    fn error(input: &str) -> (ParseKeyErrorReason, String, Range<usize>) {
+        let error = parse_send(input, &KeyboardLayout::us()).unwrap_err();
+        (error.reason(), error.token().into(), error.span())
+    }
+
+    #[test]
+    fn test_parse_send() {
+        let ctrl = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftControl);
+        let shift = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift);
+        let shift_tab =
+            KeysClick::new(KeyboardKey::Tab).add_modifier(KeyboardModifierKey::LeftShift);
+
+        let expected = KeySequence::new()
+            .add_click(ctrl.add_key(KeyboardKey::C))
+            .add_click(shift_tab)
+            .add_repeat(KeysClick::new(KeyboardKey::Enter), 3)
+            .add_press(shift)
+            .add_click(KeysClick::new(KeyboardKey::A))
+            .add_release(shift);
+
+        assert_eq!(parse("^c+{Tab}{Enter 3}{Shift down}a{shift UP}"), expected);
+    }
+
+    #[test]
+    fn test_parse_send_characters() {
+        let shift = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift);
+
+        let expected = KeySequence::new()
+            .add_click(shift.add_key(KeyboardKey::H))
+            .add_click(KeysClick::new(KeyboardKey::I))
+            .add_click(shift.add_key(KeyboardKey::LeftBracket))
+            .add_click(shift.add_key(KeyboardKey::RightBracket))
+            .add_click(KeysClick::new(KeyboardKey::Space))
+            .add_repeat(KeysClick::new(KeyboardKey::B), 2)
+            .add_click(
+                shift
+                    .add_modifier(KeyboardModifierKey::LeftAlt)
+                    .add_key(KeyboardKey::Num6),
+            )
+            .add_click(KeysClick::new(KeyboardKey::Enter))
+            .add_click(KeysClick::new(KeyboardKey::Tab));
+
+        assert_eq!(parse("Hi{{}{}}{ }{b 2}!{^}\n\t"), expected);
+        assert_eq!(parse("{Enter 0}"), KeySequence::new());
+        assert_eq!(
+            parse("#{Up}"),
+            KeySequence::new().add_click(
+                KeysClick::new(KeyboardKey::UpArrow).add_modifier(KeyboardModifierKey::LeftWindows)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_send_errors() {
+        assert_eq!(
+            error("a{Entr}"),
+            (ParseKeyErrorReason::UnknownKey, "Entr".into(), 2..6)
+        );
+        assert_eq!(
+            parse_send("{Entr}", &KeyboardLayout::us())
+                .unwrap_err()
+                .suggestion(),
+            Some("Enter")
+        );
+        assert_eq!(
+            error("{Raw}^c"),
+            (ParseKeyErrorReason::UnsupportedCommand, "Raw".into(), 1..4)
+        );
+        assert_eq!(
+            error("{Click 10 20}"),
+            (
+                ParseKeyErrorReason::UnsupportedCommand,
+                "Click".into(),
+                1..6
+            )
+        );
+        assert_eq!(
+            error("{U+20AC}"),
+            (
+                ParseKeyErrorReason::UnsupportedCommand,
+                "U+20AC".into(),
+                1..7
+            )
+        );
+        assert_eq!(
+            error("{vk41}"),
+            (ParseKeyErrorReason::UnsupportedCommand, "vk41".into(), 1..5)
+        );
+        assert_eq!(
+            error("{Tab  sideways}"),
+            (
+                ParseKeyErrorReason::UnsupportedCommand,
+                "sideways".into(),
+                6..14
+            )
+        );
+        assert_eq!(
+            error("{Tab -1}"),
+            (ParseKeyErrorReason::InvalidRepeatCount, "-1".into(), 5..7)
+        );
+        assert_eq!(
+            error("ab{Tab"),
+            (
+                ParseKeyErrorReason::MissingClosingBrace,
+                "{Tab".into(),
+                2..6
+            )
+        );
+        assert_eq!(
+            error("a}"),
+            (ParseKeyErrorReason::UnexpectedBrace, "}".into(), 1..2)
+        );
+        assert_eq!(
+            error("a^+"),
+            (ParseKeyErrorReason::MissingKeys, "^+".into(), 1..3)
+        );
+        assert_eq!(
+            error("aä"),
+            (ParseKeyErrorReason::UnmappedCharacter, "ä".into(), 1..3)
+        );
+    }
+
+    #[test]
+    fn test_parse_send_and_send() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let sequence = parse("{Alt down}{Tab 2}{Alt up}");
+
+        keyboard.send_sequence(&sequence).unwrap();
+
+        let sent: Vec<(u8, u8)> = transport.reports().iter().map(|r| (r[3], r[5])).collect();
+
+        assert_eq!(
+            sent,
+            vec![(4, 0), (4, 0x2B), (4, 0), (4, 0x2B), (4, 0), (0, 0)]
+        );
+        assert_eq!(keyboard.held(), KeysClick::empty());
+    }
+}
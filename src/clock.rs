@@ -0,0 +1,40 @@
+use std::thread;
+use std::time::Duration;
+
+/// Source of waiting between reports, replaceable to test timing without real sleeps.
+pub trait Clock {
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::clock::Clock;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct RecordingClock {
+        sleeps: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl RecordingClock {
+        pub(crate) fn sleeps(&self) -> Vec<Duration> {
+            self.sleeps.borrow().clone()
+        }
+    }
+
+    impl Clock for RecordingClock {
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+}
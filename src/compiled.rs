@@ -0,0 +1,394 @@
+use crate::clock::Clock;
+use crate::device::Transport;
+use crate::keyboard::{Keyboard, KeyboardError};
+use crate::mouse::{Mouse, MouseClick};
+use crate::sequence::KeySequence;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::mem::take;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+const MAGIC: [u8; 4] = *b"VMCS";
+const VERSION: u8 = 1;
+
+const REPORT_TAG: u8 = 0;
+const WAIT_TAG: u8 = 1;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum CompiledSequenceError {
+    Io(ErrorKind),
+    InvalidHeader,
+    UnsupportedVersion(u8),
+    InvalidStep(usize),
+    Truncated,
+    ReportTooLong(usize),
+}
+
+impl Display for CompiledSequenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            CompiledSequenceError::Io(kind) => write!(f, "failed to access file: {:?}", kind),
+            CompiledSequenceError::InvalidHeader => write!(f, "not a compiled sequence"),
+            CompiledSequenceError::UnsupportedVersion(version) => {
+                write!(f, "unsupported compiled sequence version {}", version)
+            }
+            CompiledSequenceError::InvalidStep(offset) => {
+                write!(f, "invalid step at byte {}", offset)
+            }
+            CompiledSequenceError::Truncated => write!(f, "compiled sequence is truncated"),
+            CompiledSequenceError::ReportTooLong(length) => {
+                write!(f, "report of {} bytes does not fit in a step", length)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+enum Step {
+    Report(Vec<u8>),
+    Wait(Duration),
+}
+
+// Transport and clock keeping what a keyboard sends, in order.
+#[derive(Clone, Default)]
+struct Recorder {
+    steps: Rc<RefCell<Vec<Step>>>,
+}
+
+impl Transport for Recorder {
+    fn send_report(&self, report: &[u8]) -> bool {
+        self.steps.borrow_mut().push(Step::Report(report.to_vec()));
+        true
+    }
+}
+
+impl Clock for Recorder {
+    fn sleep(&self, duration: Duration) {
+        if duration > Duration::from_millis(0) {
+            self.steps.borrow_mut().push(Step::Wait(duration));
+        }
+    }
+}
+
+/// Encoded reports and the waits between them, built once and sent as many times as needed.
+///
+/// The binary form starts with "VMCS" and a version byte, followed by the steps: a zero byte,
+/// the report length and the report, or a one byte and the wait in nanoseconds as a LEB128
+/// varint.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct CompiledSequence {
+    steps: Vec<Step>,
+}
+
+impl CompiledSequence {
+    pub fn new() -> Self {
+        CompiledSequence::default()
+    }
+
+    /// Adds the reports the keyboard would send for the sequence, starting with no key held.
+    pub fn add_key_sequence(
+        mut self,
+        keyboard: &Keyboard,
+        sequence: &KeySequence,
+    ) -> Result<Self, KeyboardError> {
+        let recorder = Recorder::default();
+        let recording = keyboard.with_transport(recorder.clone(), recorder.clone());
+
+        recording.send_sequence(sequence)?;
+
+        // Taken before the recording keyboard releases its keys on drop.
+        self.steps.extend(take(&mut *recorder.steps.borrow_mut()));
+        Ok(self)
+    }
+
+    pub fn add_mouse_click(mut self, mouse: &Mouse, click: MouseClick) -> Self {
+        let report = mouse.click_report(&click);
+
+        self.steps.push(Step::Report(report.to_vec()));
+        self
+    }
+
+    pub fn add_wait(mut self, duration: Duration) -> Self {
+        self.steps.push(Step::Wait(duration));
+        self
+    }
+
+    /// Sends every report and waits in between, stopping at the first report that fails.
+    pub fn send<T: Transport + ?Sized, C: Clock + ?Sized>(&self, transport: &T, clock: &C) -> bool {
+        for step in &self.steps {
+            match step {
+                Step::Report(report) => {
+                    if !transport.send_report(report) {
+                        return false;
+                    }
+                }
+                Step::Wait(duration) => clock.sleep(*duration),
+            }
+        }
+
+        true
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CompiledSequenceError> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        for step in &self.steps {
+            match step {
+                Step::Report(report) => {
+                    let length = u8::try_from(report.len())
+                        .map_err(|_| CompiledSequenceError::ReportTooLong(report.len()))?;
+
+                    bytes.push(REPORT_TAG);
+                    bytes.push(length);
+                    bytes.extend_from_slice(report);
+                }
+                Step::Wait(duration) => {
+                    bytes.push(WAIT_TAG);
+                    write_varint(&mut bytes, duration.as_nanos().min(u64::MAX as u128) as u64);
+                }
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CompiledSequenceError> {
+        if bytes.len() < MAGIC.len() + 1 || bytes[..MAGIC.len()] != MAGIC {
+            return Err(CompiledSequenceError::InvalidHeader);
+        }
+
+        let version = bytes[MAGIC.len()];
+
+        if version != VERSION {
+            return Err(CompiledSequenceError::UnsupportedVersion(version));
+        }
+
+        let mut steps = Vec::new();
+        let mut position = MAGIC.len() + 1;
+
+        while position < bytes.len() {
+            let offset = position;
+            position += 1;
+
+            match bytes[offset] {
+                REPORT_TAG => {
+                    let length = *bytes
+                        .get(position)
+                        .ok_or(CompiledSequenceError::Truncated)?
+                        as usize;
+                    let report = bytes
+                        .get(position + 1..position + 1 + length)
+                        .ok_or(CompiledSequenceError::Truncated)?;
+
+                    steps.push(Step::Report(report.to_vec()));
+                    position += 1 + length;
+                }
+                WAIT_TAG => {
+                    let (nanos, length) = read_varint(&bytes[position..], offset)?;
+
+                    steps.push(Step::Wait(Duration::from_nanos(nanos)));
+                    position += length;
+                }
+                _ => return Err(CompiledSequenceError::InvalidStep(offset)),
+            }
+        }
+
+        Ok(CompiledSequence { steps })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CompiledSequenceError> {
+        fs::write(path, self.to_bytes()?).map_err(|e| CompiledSequenceError::Io(e.kind()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CompiledSequenceError> {
+        let bytes = fs::read(path).map_err(|e| CompiledSequenceError::Io(e.kind()))?;
+
+        CompiledSequence::from_bytes(&bytes)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+// Returns the value and the number of bytes it took, the step at the offset being invalid when
+// the value overflows.
+fn read_varint(bytes: &[u8], offset: usize) -> Result<(u64, usize), CompiledSequenceError> {
+    let mut value = 0u64;
+
+    for (index, byte) in bytes.iter().enumerate() {
+        let bits = (*byte & 0x7F) as u64;
+
+        if index == 9 && *byte > 1 {
+            return Err(CompiledSequenceError::InvalidStep(offset));
+        }
+
+        value |= bits << (7 * index);
+
+        if byte & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+
+    Err(CompiledSequenceError::Truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::testing::RecordingClock;
+    use crate::compiled::{CompiledSequence, CompiledSequenceError, Step};
+    use crate::device::testing::RecordingTransport;
+    use crate::keyboard::{Keyboard, KeyboardKey, KeyboardModifierKey, KeysClick};
+    use crate::mouse::testing::mouse;
+    use crate::mouse::{MouseButton, MouseClick};
+    use crate::sequence::KeySequence;
+    use std::convert::TryFrom;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_compiled_key_sequence_matches_keyboard() {
+        let sequence = KeySequence::try_from("ctrl+c, wait 200ms, hold shift { a, b }").unwrap();
+
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_duration(Duration::from_millis(20));
+
+        keyboard.send_sequence(&sequence).unwrap();
+
+        let compiled = CompiledSequence::new()
+            .add_key_sequence(&keyboard, &sequence)
+            .unwrap();
+
+        let replay_transport = RecordingTransport::default();
+        let replay_clock = RecordingClock::default();
+
+        assert!(compiled.send(&replay_transport, &replay_clock));
+        assert_eq!(replay_transport.reports(), transport.reports());
+        assert_eq!(replay_clock.sleeps(), clock.sleeps());
+    }
+
+    #[test]
+    fn test_compiled_sequence_sends_many_times() {
+        let keyboard = Keyboard::from_transport(RecordingTransport::default());
+        let sequence = KeySequence::new().add_click(
+            KeysClick::new(KeyboardKey::V).add_modifier(KeyboardModifierKey::LeftControl),
+        );
+
+        let transport = RecordingTransport::default();
+        let mouse = mouse(transport.clone());
+
+        let compiled = CompiledSequence::new()
+            .add_mouse_click(&mouse, MouseClick::new().set_button(MouseButton::Left))
+            .add_mouse_click(&mouse, MouseClick::new())
+            .add_wait(Duration::from_millis(50))
+            .add_key_sequence(&keyboard, &sequence)
+            .unwrap();
+
+        mouse.send_click(MouseClick::new().set_button(MouseButton::Left));
+
+        let clock = RecordingClock::default();
+
+        for _ in 0..3 {
+            assert!(compiled.send(&transport, &clock));
+        }
+
+        let reports = transport.reports();
+
+        assert_eq!(reports.len(), 1 + 3 * 4);
+        assert_eq!(reports[1], reports[0]);
+        assert_eq!(reports[3][2..5], [0x07, 0x01, 0x00]);
+        assert_eq!(reports[1..5], reports[5..9]);
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(50); 3]);
+    }
+
+    #[test]
+    fn test_compiled_sequence_bytes() {
+        let compiled = CompiledSequence::new()
+            .add_mouse_click(&mouse(RecordingTransport::default()), MouseClick::new())
+            .add_wait(Duration::from_millis(200))
+            .add_wait(Duration::from_nanos(1));
+
+        let bytes = compiled.to_bytes().unwrap();
+
+        assert_eq!(bytes[..7], *b"VMCS\x01\x00\x0A");
+        assert_eq!(bytes[17..], [0x01, 0x80, 0x84, 0xAF, 0x5F, 0x01, 0x01]);
+        assert_eq!(CompiledSequence::from_bytes(&bytes), Ok(compiled));
+
+        let too_long = CompiledSequence {
+            steps: vec![Step::Report(vec![0; 256])],
+        };
+
+        assert_eq!(
+            too_long.to_bytes(),
+            Err(CompiledSequenceError::ReportTooLong(256))
+        );
+    }
+
+    #[test]
+    fn test_compiled_sequence_bytes_errors() {
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMC"),
+            Err(CompiledSequenceError::InvalidHeader)
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x02"),
+            Err(CompiledSequenceError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x01\x00\x03\x01\x02"),
+            Err(CompiledSequenceError::Truncated)
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x01\x01\x80"),
+            Err(CompiledSequenceError::Truncated)
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x01\x01\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x02"),
+            Err(CompiledSequenceError::InvalidStep(5))
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x01\x01\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x81"),
+            Err(CompiledSequenceError::InvalidStep(5))
+        );
+        assert_eq!(
+            CompiledSequence::from_bytes(b"VMCS\x01\x01\x05\x07"),
+            Err(CompiledSequenceError::InvalidStep(7))
+        );
+    }
+
+    #[test]
+    fn test_compiled_sequence_file() {
+        // Named after the process, so that concurrent test runs do not share the file.
+        let name = format!("vmulti-client-compiled-sequence-{}.bin", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let keyboard = Keyboard::from_transport(RecordingTransport::default());
+
+        let compiled = CompiledSequence::new()
+            .add_key_sequence(&keyboard, &KeySequence::try_from("enter x3").unwrap())
+            .unwrap();
+
+        compiled.save(&path).unwrap();
+        let loaded = CompiledSequence::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, Ok(compiled));
+        assert_eq!(
+            CompiledSequence::load(&path),
+            Err(CompiledSequenceError::Io(std::io::ErrorKind::NotFound))
+        );
+    }
+}
@@ -1,40 +1,119 @@
-use crate::keyboard::KeysClick;
-use serde::de::Visitor;
-use serde::de::{Error, Unexpected};
+use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick, ParseKeyError};
+use crate::mouse::MouseButton;
+use crate::sequence::KeySequence;
+use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer};
 use std::convert::TryFrom;
 use std::fmt;
+use std::marker::PhantomData;
 
-struct KeysClickVisitor;
+const MOUSE_BUTTON_NAMES: &[&str] = &["left", "right", "middle"];
 
-impl<'de> Visitor<'de> for KeysClickVisitor {
-    type Value = KeysClick;
+// Strings read with the parsers of the keyboard types.
+struct ParseVisitor<T> {
+    name: &'static str,
+    expecting: &'static str,
+    marker: PhantomData<T>,
+}
+
+impl<T> ParseVisitor<T> {
+    fn new(name: &'static str, expecting: &'static str) -> Self {
+        ParseVisitor {
+            name,
+            expecting,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> Visitor<'de> for ParseVisitor<T>
+where
+    T: for<'a> TryFrom<&'a str, Error = ParseKeyError>,
+{
+    type Value = T;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            formatter,
-            "an string of keyboard modifiers and keys separated with +"
-        )
+        write!(formatter, "{}", self.expecting)
     }
 
     fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
-        match KeysClick::try_from(v) {
-            Ok(k) => Ok(k),
-            Err(_) => Err(Error::invalid_value(Unexpected::Str(v), &self)),
-        }
+        T::try_from(v)
+            .map_err(|e| Error::custom(format_args!("invalid {} {:?}: {}", self.name, v, e)))
     }
 }
 
 impl<'de> Deserialize<'de> for KeysClick {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        deserializer.deserialize_str(KeysClickVisitor)
+        deserializer.deserialize_str(ParseVisitor::new(
+            "keys click",
+            "an string of keyboard modifiers and keys separated with +",
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeySequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor::new(
+            "key sequence",
+            "an string of key actions separated with ,",
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyboardKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor::new("key", "a keyboard key name"))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyboardModifierKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor::new("modifier", "a keyboard modifier name"))
     }
 }
 
+struct MouseButtonVisitor;
+
+impl<'de> Visitor<'de> for MouseButtonVisitor {
+    type Value = MouseButton;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a mouse button name")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        match v {
+            "left" => Ok(MouseButton::Left),
+            "right" => Ok(MouseButton::Right),
+            "middle" => Ok(MouseButton::Middle),
+            _ => Err(Error::unknown_variant(v, MOUSE_BUTTON_NAMES)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MouseButton {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(MouseButtonVisitor)
+    }
+}
+
+// Pressed buttons of a mouse click, read from a list of button names.
+pub(crate) fn deserialize_mouse_buttons<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u8, D::Error> {
+    let buttons = Vec::<MouseButton>::deserialize(deserializer)?;
+
+    Ok(buttons.iter().fold(0, |mask, button| mask | button.mask()))
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::display::{DisplayInfo, DisplayPosition};
     use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
-    use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
+    use crate::mouse::{MouseButton, MouseClick, MousePosition};
+    use crate::sequence::KeySequence;
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+    use std::time::Duration;
 
     #[test]
     fn test_keys_click_deserialize_ok() {
@@ -50,17 +129,229 @@ mod tests {
         assert_de_tokens(&keys_click1, &[Token::String("l")]);
         assert_de_tokens(&keys_click2, &[Token::String("right-shift+h")]);
         assert_de_tokens(&keys_click3, &[Token::String("h+e+l")]);
+        assert_tokens(&KeysClick::empty(), &[Token::Str("none")]);
     }
 
     #[test]
     fn test_keys_click_deserialize_error() {
         assert_de_tokens_error::<KeysClick>(
             &[Token::Str("")],
-            "invalid value: string \"\", expected an string of keyboard modifiers and keys separated with +" ,
+            "invalid keys click \"\": empty key name at 0..0",
         );
         assert_de_tokens_error::<KeysClick>(
             &[Token::Str("test")],
-            "invalid value: string \"test\", expected an string of keyboard modifiers and keys separated with +" ,
+            "invalid keys click \"test\": unknown key \"test\" at 0..4, did you mean \"esc\"?",
+        );
+    }
+
+    #[test]
+    fn test_key_sequence_deserialize_ok() {
+        let sequence = KeySequence::new()
+            .add_click(
+                KeysClick::new(KeyboardKey::C).add_modifier(KeyboardModifierKey::LeftControl),
+            )
+            .add_wait(Duration::from_millis(200))
+            .add_hold(
+                KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift),
+                KeySequence::new().add_click(KeysClick::new(KeyboardKey::A)),
+            );
+
+        assert_de_tokens(
+            &sequence,
+            &[Token::String(
+                "left-ctrl+c, wait 200ms, hold left-shift { a }",
+            )],
+        );
+    }
+
+    #[test]
+    fn test_key_sequence_deserialize_error() {
+        assert_de_tokens_error::<KeySequence>(
+            &[Token::Str("wait")],
+            "invalid key sequence \"wait\": missing duration \"wait\" at 0..4",
+        );
+    }
+
+    #[test]
+    fn test_keys_deserialize() {
+        assert_de_tokens(&KeyboardKey::PageUp, &[Token::Str("page-up")]);
+        assert_de_tokens(&KeyboardKey::Enter, &[Token::Str("Return")]);
+        assert_de_tokens(&KeyboardModifierKey::LeftControl, &[Token::Str("ctrl")]);
+        assert_de_tokens_error::<KeyboardKey>(
+            &[Token::Str("entr")],
+            "invalid key \"entr\": unknown key \"entr\" at 0..4, did you mean \"enter\"?",
+        );
+    }
+
+    #[test]
+    fn test_mouse_button_deserialize() {
+        assert_tokens(&MouseButton::Right, &[Token::Str("right")]);
+        assert_de_tokens_error::<MouseButton>(
+            &[Token::Str("back")],
+            "unknown variant `back`, expected one of `left`, `right`, `middle`",
+        );
+    }
+
+    #[test]
+    fn test_mouse_click_deserialize() {
+        let click = MouseClick::new()
+            .set_position(10, 20)
+            .set_button(MouseButton::Right)
+            .set_wheel_position(3);
+
+        assert_de_tokens(
+            &click,
+            &[
+                Token::Struct {
+                    name: "MouseClick",
+                    len: 5,
+                },
+                Token::Str("buttons"),
+                Token::Seq { len: Some(1) },
+                Token::Str("right"),
+                Token::SeqEnd,
+                Token::Str("x"),
+                Token::U16(10),
+                Token::Str("y"),
+                Token::U16(20),
+                Token::Str("wheel_position"),
+                Token::U8(3),
+                Token::Str("display_index"),
+                Token::None,
+                Token::StructEnd,
+            ],
+        );
+        assert_de_tokens(
+            &MouseClick::new().set_position(1, 2),
+            &[
+                Token::Struct {
+                    name: "MouseClick",
+                    len: 2,
+                },
+                Token::Str("x"),
+                Token::U16(1),
+                Token::Str("y"),
+                Token::U16(2),
+                Token::StructEnd,
+            ],
+        );
+        assert_de_tokens_error::<MouseClick>(
+            &[
+                Token::Struct {
+                    name: "MouseClick",
+                    len: 1,
+                },
+                Token::Str("z"),
+            ],
+            "unknown field `z`, expected one of `buttons`, `x`, `y`, `wheel_position`, `display_index`",
+        );
+        assert_de_tokens_error::<MouseClick>(
+            &[
+                Token::Struct {
+                    name: "MouseClick",
+                    len: 2,
+                },
+                Token::Str("x"),
+                Token::U16(1),
+                Token::Str("x"),
+            ],
+            "duplicate field `x`",
+        );
+    }
+
+    #[test]
+    fn test_mouse_position_deserialize() {
+        let position = MousePosition {
+            display_index: 1,
+            x: 5,
+            y: 7,
+        };
+
+        assert_tokens(
+            &position,
+            &[
+                Token::Struct {
+                    name: "MousePosition",
+                    len: 3,
+                },
+                Token::Str("display_index"),
+                Token::U64(1),
+                Token::Str("x"),
+                Token::U16(5),
+                Token::Str("y"),
+                Token::U16(7),
+                Token::StructEnd,
+            ],
+        );
+        assert_de_tokens_error::<MousePosition>(
+            &[
+                Token::Struct {
+                    name: "MousePosition",
+                    len: 1,
+                },
+                Token::Str("x"),
+                Token::U16(5),
+                Token::StructEnd,
+            ],
+            "missing field `display_index`",
+        );
+
+        // Formats which are not self-describing write structs as sequences.
+        assert_de_tokens(
+            &position,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::U64(1),
+                Token::U16(5),
+                Token::U16(7),
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_display_info_deserialize() {
+        let display_info = DisplayInfo {
+            primary: false,
+            position: DisplayPosition {
+                left: 1920,
+                top: 0,
+                right: 3840,
+                bottom: 1080,
+            },
+            width: 1920,
+            height: 1080,
+        };
+
+        assert_tokens(
+            &display_info,
+            &[
+                Token::Struct {
+                    name: "DisplayInfo",
+                    len: 4,
+                },
+                Token::Str("primary"),
+                Token::Bool(false),
+                Token::Str("position"),
+                Token::Struct {
+                    name: "DisplayPosition",
+                    len: 4,
+                },
+                Token::Str("left"),
+                Token::I32(1920),
+                Token::Str("top"),
+                Token::I32(0),
+                Token::Str("right"),
+                Token::I32(3840),
+                Token::Str("bottom"),
+                Token::I32(1080),
+                Token::StructEnd,
+                Token::Str("width"),
+                Token::U16(1920),
+                Token::Str("height"),
+                Token::U16(1080),
+                Token::StructEnd,
+            ],
         );
     }
 }
@@ -1,27 +1,47 @@
+#[cfg(windows)]
 use std::mem::MaybeUninit;
+#[cfg(windows)]
 use std::ptr::null_mut;
 
+#[cfg(windows)]
 use winapi::shared::guiddef::GUID;
+#[cfg(windows)]
 use winapi::shared::hidpi::{HidP_GetCaps, PHIDP_PREPARSED_DATA};
+#[cfg(windows)]
 use winapi::shared::hidpi::{HIDP_CAPS, HIDP_STATUS_SUCCESS};
+#[cfg(windows)]
 use winapi::shared::hidsdi::{HidD_GetAttributes, HidD_GetHidGuid};
+#[cfg(windows)]
 use winapi::shared::hidsdi::{HidD_GetPreparsedData, HIDD_ATTRIBUTES};
+#[cfg(windows)]
 use winapi::shared::minwindef::DWORD;
+#[cfg(windows)]
 use winapi::shared::ntdef::{HANDLE, PVOID, ULONG};
+#[cfg(windows)]
 use winapi::um::cfgmgr32::{
     CM_Get_Device_Interface_ListW, CM_Get_Device_Interface_List_SizeA,
     CM_GET_DEVICE_INTERFACE_LIST_PRESENT, CR_SUCCESS,
 };
+#[cfg(windows)]
 use winapi::um::fileapi::OPEN_EXISTING;
+#[cfg(windows)]
 use winapi::um::fileapi::{CreateFileA, WriteFile};
+#[cfg(windows)]
 use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+#[cfg(windows)]
 use winapi::um::winnt::FILE_SHARE_WRITE;
+#[cfg(windows)]
 use winapi::um::winnt::GENERIC_WRITE;
 
+#[cfg(windows)]
 const DEVICE_VENDOR_ID: u16 = 0x00FF;
+#[cfg(windows)]
 const DEVICE_PRODUCT_ID: u16 = 0xBACC;
+#[cfg(windows)]
 const DEVICE_VERSION_ID: u16 = 0x0001;
+#[cfg(windows)]
 const DEVICE_USAGE_PAGE: u16 = 0xFF00;
+#[cfg(windows)]
 const DEVICE_USAGE: u16 = 0x0001;
 
 pub const CONTROL_REPORT_ID: u8 = 0x40;
@@ -36,26 +56,47 @@ pub enum DeviceError {
     NotFound,
 }
 
+/// Destination of encoded reports, the vmulti device unless replaced.
+pub trait Transport {
+    fn send_report(&self, report: &[u8]) -> bool;
+}
+
+#[cfg(windows)]
 pub struct Device {
     handle: HANDLE,
 }
 
+#[cfg(windows)]
 impl Device {
-    pub(crate) fn send_report(&self, data: PVOID) -> bool {
+    /// Opens the vmulti device, to send reports that were encoded beforehand.
+    pub fn open() -> Result<Self, DeviceError> {
+        find_device()
+    }
+}
+
+#[cfg(windows)]
+impl Transport for Device {
+    fn send_report(&self, report: &[u8]) -> bool {
+        // The driver always reads a full control report.
+        let mut data = [0u8; CONTROL_REPORT_SIZE as usize];
+        let length = report.len().min(data.len());
+        data[..length].copy_from_slice(&report[..length]);
+
         let mut bytes_written = MaybeUninit::<DWORD>::uninit();
 
         unsafe {
-            return WriteFile(
+            WriteFile(
                 self.handle,
-                data,
+                data.as_mut_ptr() as PVOID,
                 CONTROL_REPORT_SIZE,
                 bytes_written.as_mut_ptr(),
                 null_mut(),
-            ) == 1;
+            ) == 1
         }
     }
 }
 
+#[cfg(windows)]
 impl Drop for Device {
     fn drop(&mut self) {
         unsafe {
@@ -64,6 +105,7 @@ impl Drop for Device {
     }
 }
 
+#[cfg(windows)]
 pub(crate) fn find_device() -> Result<Device, DeviceError> {
     let mut maybe_guid = MaybeUninit::<GUID>::uninit();
 
@@ -74,6 +116,7 @@ pub(crate) fn find_device() -> Result<Device, DeviceError> {
     Ok(Device { handle })
 }
 
+#[cfg(windows)]
 unsafe fn find_handle(mut guid: GUID) -> Result<HANDLE, DeviceError> {
     let mut maybe_device_interface_list_length = MaybeUninit::<ULONG>::uninit();
 
@@ -147,6 +190,7 @@ unsafe fn find_handle(mut guid: GUID) -> Result<HANDLE, DeviceError> {
     Err(DeviceError::NotFound)
 }
 
+#[cfg(windows)]
 unsafe fn is_our_device(handle: HANDLE) -> bool {
     let mut maybe_attributes = MaybeUninit::<HIDD_ATTRIBUTES>::uninit();
 
@@ -194,3 +238,37 @@ unsafe fn is_our_device(handle: HANDLE) -> bool {
 
     return true;
 }
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::device::Transport;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct RecordingTransport {
+        reports: Rc<RefCell<Vec<Vec<u8>>>>,
+    }
+
+    impl RecordingTransport {
+        pub(crate) fn reports(&self) -> Vec<Vec<u8>> {
+            self.reports.borrow().clone()
+        }
+    }
+
+    impl Transport for RecordingTransport {
+        fn send_report(&self, report: &[u8]) -> bool {
+            self.reports.borrow_mut().push(report.to_vec());
+            true
+        }
+    }
+
+    /// Transport whose every send fails.
+    pub(crate) struct FailingTransport;
+
+    impl Transport for FailingTransport {
+        fn send_report(&self, _report: &[u8]) -> bool {
+            false
+        }
+    }
+}
@@ -1,15 +1,30 @@
+#[cfg(windows)]
 use std::mem::size_of;
+#[cfg(windows)]
 use std::ptr::null_mut;
+#[cfg(windows)]
 use winapi::shared::minwindef::BOOL;
+#[cfg(windows)]
 use winapi::shared::minwindef::LPARAM;
+#[cfg(windows)]
 use winapi::shared::windef::HDC;
+#[cfg(windows)]
 use winapi::shared::windef::HMONITOR;
+#[cfg(windows)]
 use winapi::shared::windef::LPRECT;
+#[cfg(windows)]
 use winapi::shared::windef::RECT;
+#[cfg(windows)]
 use winapi::um::winuser::MONITORINFO;
+#[cfg(windows)]
 use winapi::um::winuser::{EnumDisplayMonitors, GetMonitorInfoW};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct DisplayInfo {
     pub primary: bool,
     pub position: DisplayPosition,
@@ -17,7 +32,12 @@ pub struct DisplayInfo {
     pub height: u16,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct DisplayPosition {
     pub left: i32,
     pub top: i32,
@@ -25,6 +45,7 @@ pub struct DisplayPosition {
     pub bottom: i32,
 }
 
+#[cfg(windows)]
 pub fn get_displays_info() -> Vec<DisplayInfo> {
     let mut displays_info: Vec<DisplayInfo> = Vec::new();
 
@@ -40,6 +61,7 @@ pub fn get_displays_info() -> Vec<DisplayInfo> {
     return displays_info;
 }
 
+#[cfg(windows)]
 unsafe extern "system" fn display_info_callback(
     monitor: HMONITOR,
     _hdc: HDC,
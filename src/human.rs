@@ -0,0 +1,340 @@
+use crate::keyboard::{KeyboardKey, KeysClick};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::time::Duration;
+
+// Character keys of a US keyboard, row by row, to tell how far fingers travel between keys.
+const QWERTY_ROWS: [&[KeyboardKey]; 4] = [
+    &[
+        KeyboardKey::Grave,
+        KeyboardKey::Num1,
+        KeyboardKey::Num2,
+        KeyboardKey::Num3,
+        KeyboardKey::Num4,
+        KeyboardKey::Num5,
+        KeyboardKey::Num6,
+        KeyboardKey::Num7,
+        KeyboardKey::Num8,
+        KeyboardKey::Num9,
+        KeyboardKey::Num0,
+        KeyboardKey::Minus,
+        KeyboardKey::Equal,
+    ],
+    &[
+        KeyboardKey::Q,
+        KeyboardKey::W,
+        KeyboardKey::E,
+        KeyboardKey::R,
+        KeyboardKey::T,
+        KeyboardKey::Y,
+        KeyboardKey::U,
+        KeyboardKey::I,
+        KeyboardKey::O,
+        KeyboardKey::P,
+        KeyboardKey::LeftBracket,
+        KeyboardKey::RightBracket,
+        KeyboardKey::Backslash,
+    ],
+    &[
+        KeyboardKey::A,
+        KeyboardKey::S,
+        KeyboardKey::D,
+        KeyboardKey::F,
+        KeyboardKey::G,
+        KeyboardKey::H,
+        KeyboardKey::J,
+        KeyboardKey::K,
+        KeyboardKey::L,
+        KeyboardKey::Semicolon,
+        KeyboardKey::Quote,
+    ],
+    &[
+        KeyboardKey::Z,
+        KeyboardKey::X,
+        KeyboardKey::C,
+        KeyboardKey::V,
+        KeyboardKey::B,
+        KeyboardKey::N,
+        KeyboardKey::M,
+        KeyboardKey::Comma,
+        KeyboardKey::Period,
+        KeyboardKey::Slash,
+    ],
+];
+
+// Horizontal offset of the rows, in key widths, and the first column typed by the right hand.
+const ROW_OFFSETS: [f64; 4] = [0.0, 1.5, 1.75, 2.25];
+const RIGHT_HAND_COLUMNS: [usize; 4] = [6, 5, 5, 5];
+
+// Keys at most this far apart, in key widths, are neighbours.
+const ADJACENT_DISTANCE: f64 = 1.3;
+
+/// Timing and mistakes of a human typist, replacing the delay and jitter of the typing options.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HumanModel {
+    words_per_minute: u32,
+    variance: f64,
+    typo_rate: f64,
+    correction_delay: Duration,
+}
+
+impl HumanModel {
+    pub fn new() -> Self {
+        HumanModel {
+            words_per_minute: 60,
+            variance: 0.3,
+            typo_rate: 0.0,
+            correction_delay: Duration::from_millis(300),
+        }
+    }
+
+    /// Typing speed, counting five characters per word, the time keys are held included.
+    pub fn set_words_per_minute(mut self, words_per_minute: u32) -> Self {
+        self.words_per_minute = words_per_minute.max(1);
+        self
+    }
+
+    /// Randomizes every pause within the given fraction of itself, once the key pair is
+    /// accounted for.
+    pub fn set_variance(mut self, variance: f64) -> Self {
+        self.variance = variance.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Chance for a character to be typed with a neighbouring key first, then erased with
+    /// backspace.
+    pub fn set_typo_rate(mut self, typo_rate: f64) -> Self {
+        self.typo_rate = typo_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Pause before erasing a typo.
+    pub fn set_correction_delay(mut self, correction_delay: Duration) -> Self {
+        self.correction_delay = correction_delay;
+        self
+    }
+
+    /// Inserts typos and their corrections among the keystrokes of every character, along with
+    /// the pauses the model does not choose itself.
+    pub(crate) fn add_typos(
+        &self,
+        characters: Vec<Vec<KeysClick>>,
+        rng: &mut StdRng,
+    ) -> Vec<(KeysClick, Option<Duration>)> {
+        let mut keys_clicks = Vec::new();
+
+        for strokes in characters {
+            // Only characters typed with a single keystroke are mistyped.
+            let typo = match strokes.as_slice() {
+                [stroke, _] if rng.gen_bool(self.typo_rate) => typo(*stroke, rng),
+                _ => None,
+            };
+
+            if let Some(typo) = typo {
+                keys_clicks.push((typo, None));
+                keys_clicks.push((KeysClick::empty(), Some(self.correction_delay)));
+                keys_clicks.push((KeysClick::new(KeyboardKey::Del), None));
+                keys_clicks.push((KeysClick::empty(), None));
+            }
+
+            keys_clicks.extend(strokes.into_iter().map(|stroke| (stroke, None)));
+        }
+
+        keys_clicks
+    }
+
+    /// Pause between releasing the previous key and pressing the next one, what is left of the
+    /// interval between keystrokes once the previous key was held.
+    pub(crate) fn pause(
+        &self,
+        previous: Option<KeyboardKey>,
+        next: Option<KeyboardKey>,
+        hold_duration: Duration,
+        rng: &mut StdRng,
+    ) -> Duration {
+        let interval = 12.0 / self.words_per_minute as f64;
+        let noise = 1.0 + rng.gen_range(-1.0..=1.0) * self.variance;
+
+        Duration::from_secs_f64(interval * pair_factor(previous, next) * noise)
+            .saturating_sub(hold_duration)
+    }
+}
+
+impl Default for HumanModel {
+    fn default() -> Self {
+        HumanModel::new()
+    }
+}
+
+fn key_position(key: KeyboardKey) -> Option<(usize, usize)> {
+    QWERTY_ROWS.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter()
+            .position(|k| *k == key)
+            .map(|column| (row, column))
+    })
+}
+
+fn distance((row1, column1): (usize, usize), (row2, column2): (usize, usize)) -> f64 {
+    let x = (ROW_OFFSETS[row1] + column1 as f64) - (ROW_OFFSETS[row2] + column2 as f64);
+    let y = row1 as f64 - row2 as f64;
+
+    (x * x + y * y).sqrt()
+}
+
+fn is_right_hand((row, column): (usize, usize)) -> bool {
+    column >= RIGHT_HAND_COLUMNS[row]
+}
+
+// Keys around the given one on a US keyboard.
+fn adjacent_keys(key: KeyboardKey) -> Vec<KeyboardKey> {
+    let position = match key_position(key) {
+        Some(position) => position,
+        None => return Vec::new(),
+    };
+
+    QWERTY_ROWS
+        .iter()
+        .enumerate()
+        .flat_map(|(row, keys)| keys.iter().enumerate().map(move |(c, k)| ((row, c), *k)))
+        .filter(|(p, k)| *k != key && distance(position, *p) <= ADJACENT_DISTANCE)
+        .map(|(_, k)| k)
+        .collect()
+}
+
+// Alternating hands is the fastest, then moving a finger of the same hand, further keys being
+// slower, and striking the same key twice is the slowest.
+fn pair_factor(previous: Option<KeyboardKey>, next: Option<KeyboardKey>) -> f64 {
+    match (previous.and_then(key_position), next.and_then(key_position)) {
+        (Some(a), Some(b)) if a == b => 1.2,
+        (Some(a), Some(b)) if is_right_hand(a) != is_right_hand(b) => 0.8,
+        (Some(a), Some(b)) => (0.9 + 0.05 * distance(a, b)).min(1.1),
+        _ => 1.0,
+    }
+}
+
+// The keystroke with a neighbouring key in place of its own, keeping its modifiers.
+fn typo(stroke: KeysClick, rng: &mut StdRng) -> Option<KeysClick> {
+    let key = stroke.keys().next()?;
+    let wrong = *adjacent_keys(key).choose(rng)?;
+
+    Some(stroke.remove_key(key).add_key(wrong))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::human::{adjacent_keys, pair_factor, HumanModel};
+    use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    #[test]
+    fn test_adjacent_keys() {
+        let mut keys = adjacent_keys(KeyboardKey::F);
+        keys.sort();
+
+        assert_eq!(
+            keys,
+            vec![
+                KeyboardKey::C,
+                KeyboardKey::D,
+                KeyboardKey::G,
+                KeyboardKey::R,
+                KeyboardKey::T,
+                KeyboardKey::V,
+            ]
+        );
+        assert!(adjacent_keys(KeyboardKey::Q).contains(&KeyboardKey::Num1));
+        assert!(adjacent_keys(KeyboardKey::Enter).is_empty());
+    }
+
+    #[test]
+    fn test_pair_factor() {
+        let same_key = pair_factor(Some(KeyboardKey::E), Some(KeyboardKey::E));
+        let other_hand = pair_factor(Some(KeyboardKey::E), Some(KeyboardKey::I));
+        let same_hand = pair_factor(Some(KeyboardKey::E), Some(KeyboardKey::R));
+        let far = pair_factor(Some(KeyboardKey::Q), Some(KeyboardKey::B));
+
+        assert!(other_hand < same_hand);
+        assert!(same_hand < far);
+        assert!(far < same_key);
+        assert_eq!(pair_factor(None, Some(KeyboardKey::E)), 1.0);
+    }
+
+    #[test]
+    fn test_pause_follows_words_per_minute() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let model = HumanModel::new()
+            .set_words_per_minute(120)
+            .set_variance(0.0);
+
+        let no_hold = Duration::from_millis(0);
+
+        assert_eq!(
+            model.pause(None, Some(KeyboardKey::A), no_hold, &mut rng),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            model.pause(
+                None,
+                Some(KeyboardKey::A),
+                Duration::from_millis(30),
+                &mut rng
+            ),
+            Duration::from_millis(70)
+        );
+        assert_eq!(
+            model.pause(
+                None,
+                Some(KeyboardKey::A),
+                Duration::from_millis(150),
+                &mut rng
+            ),
+            Duration::from_millis(0)
+        );
+
+        let model = model.set_variance(0.5);
+
+        for _ in 0..100 {
+            let pause = model.pause(None, Some(KeyboardKey::A), no_hold, &mut rng);
+
+            assert!(pause >= Duration::from_millis(50));
+            assert!(pause <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn test_add_typos() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let shift_a = KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::LeftShift);
+
+        let model = HumanModel::new()
+            .set_typo_rate(1.0)
+            .set_correction_delay(Duration::from_millis(400));
+
+        let keys_clicks = model.add_typos(vec![vec![shift_a, KeysClick::empty()]], &mut rng);
+        let (typo, _) = keys_clicks[0];
+
+        assert_eq!(typo.modifiers(), shift_a.modifiers());
+        assert!(adjacent_keys(KeyboardKey::A).contains(&typo.keys().next().unwrap()));
+        assert_eq!(
+            keys_clicks[1..],
+            [
+                (KeysClick::empty(), Some(Duration::from_millis(400))),
+                (KeysClick::new(KeyboardKey::Del), None),
+                (KeysClick::empty(), None),
+                (shift_a, None),
+                (KeysClick::empty(), None),
+            ]
+        );
+
+        let model = model.set_typo_rate(0.0);
+
+        assert_eq!(
+            model.add_typos(vec![vec![shift_a, KeysClick::empty()]], &mut rng),
+            vec![(shift_a, None), (KeysClick::empty(), None)]
+        );
+    }
+}
@@ -1,16 +1,34 @@
-use crate::device::{find_device, Device, DeviceError, CONTROL_REPORT_ID};
+use crate::clock::{Clock, SystemClock};
+#[cfg(windows)]
+use crate::device::{find_device, DeviceError};
+use crate::device::{Transport, CONTROL_REPORT_ID};
+use crate::layout::{ImeLayout, KeyboardLayout};
+#[cfg(not(windows))]
+use crate::lock::LockState;
+use crate::lock::LockStateProvider;
+#[cfg(windows)]
+use crate::lock::SystemLockState;
+use crate::sequence::{KeyAction, KeySequence};
+use crate::typing::{timed_keys_clicks, uses_alt_numpad, TypingOptions};
+use bitflags::bitflags;
 use itertools::concat;
 use itertools::join;
+use log::warn;
+use std::cell::Cell;
 use std::convert::TryFrom;
-use std::fmt::Display;
+use std::fmt::{Display, Error, Formatter};
 use std::mem::size_of;
-use winapi::_core::fmt::{Error, Formatter};
-use winapi::ctypes::c_void;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Duration;
 
 const KEYBOARD_REPORT_ID: u8 = 0x07;
 const KEYBOARD_REPORT_SIZE: u8 = size_of::<KeyboardReport>() as u8;
+pub(crate) const KEYBOARD_ROLLOVER: usize = 6;
+// Reported in every key slot when more keys are pressed than a report can hold.
+const ERROR_ROLL_OVER: u8 = 0x01;
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
 pub enum KeyboardKey {
     A,
     B,
@@ -53,12 +71,94 @@ pub enum KeyboardKey {
     Del,
     Tab,
     Space,
+    Minus,
+    Equal,
+    LeftBracket,
+    RightBracket,
+    Backslash,
+    NonUsHash,
+    Semicolon,
+    Quote,
+    Grave,
+    Comma,
+    Period,
+    Slash,
+    CapsLock,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    Insert,
+    Home,
+    PageUp,
+    DeleteForward,
+    End,
+    PageDown,
+    RightArrow,
+    LeftArrow,
+    DownArrow,
+    UpArrow,
+    NumLock,
+    KeypadSlash,
+    KeypadAsterisk,
+    KeypadMinus,
+    KeypadPlus,
+    KeypadEnter,
+    Keypad1,
+    Keypad2,
+    Keypad3,
+    Keypad4,
+    Keypad5,
+    Keypad6,
+    Keypad7,
+    Keypad8,
+    Keypad9,
+    Keypad0,
+    KeypadPeriod,
+    NonUsBackslash,
+    Application,
+    // Media keys are past usage 0x65, the last one of the stock vmulti keyboard descriptor,
+    // and are only sent by drivers whose descriptor reaches usage 0x81.
+    Mute,
+    VolumeUp,
+    VolumeDown,
+    // International and language keys switch the input modes of Japanese and Korean layouts.
+    // Like media keys they are past usage 0x65, so the stock vmulti descriptor drops them.
+    International1,
+    International2,
+    International3,
+    International4,
+    International5,
+    International6,
+    International7,
+    International8,
+    International9,
+    Lang1,
+    Lang2,
+    Lang3,
+    Lang4,
+    Lang5,
+    Lang6,
+    Lang7,
+    Lang8,
+    Lang9,
 }
 
 impl KeyboardKey {
     // Key codes may be found at table 12.
     // https://www.usb.org/sites/default/files/documents/hut1_12v2.pdf
-    fn id(&self) -> u8 {
+    const fn id(&self) -> u8 {
         return match &self {
             KeyboardKey::A => 0x04,
             KeyboardKey::B => 0x05,
@@ -101,11 +201,89 @@ impl KeyboardKey {
             KeyboardKey::Del => 0x2A,
             KeyboardKey::Tab => 0x2B,
             KeyboardKey::Space => 0x2C,
+            KeyboardKey::Minus => 0x2D,
+            KeyboardKey::Equal => 0x2E,
+            KeyboardKey::LeftBracket => 0x2F,
+            KeyboardKey::RightBracket => 0x30,
+            KeyboardKey::Backslash => 0x31,
+            KeyboardKey::NonUsHash => 0x32,
+            KeyboardKey::Semicolon => 0x33,
+            KeyboardKey::Quote => 0x34,
+            KeyboardKey::Grave => 0x35,
+            KeyboardKey::Comma => 0x36,
+            KeyboardKey::Period => 0x37,
+            KeyboardKey::Slash => 0x38,
+            KeyboardKey::CapsLock => 0x39,
+            KeyboardKey::F1 => 0x3A,
+            KeyboardKey::F2 => 0x3B,
+            KeyboardKey::F3 => 0x3C,
+            KeyboardKey::F4 => 0x3D,
+            KeyboardKey::F5 => 0x3E,
+            KeyboardKey::F6 => 0x3F,
+            KeyboardKey::F7 => 0x40,
+            KeyboardKey::F8 => 0x41,
+            KeyboardKey::F9 => 0x42,
+            KeyboardKey::F10 => 0x43,
+            KeyboardKey::F11 => 0x44,
+            KeyboardKey::F12 => 0x45,
+            KeyboardKey::PrintScreen => 0x46,
+            KeyboardKey::ScrollLock => 0x47,
+            KeyboardKey::Pause => 0x48,
+            KeyboardKey::Insert => 0x49,
+            KeyboardKey::Home => 0x4A,
+            KeyboardKey::PageUp => 0x4B,
+            KeyboardKey::DeleteForward => 0x4C,
+            KeyboardKey::End => 0x4D,
+            KeyboardKey::PageDown => 0x4E,
+            KeyboardKey::RightArrow => 0x4F,
+            KeyboardKey::LeftArrow => 0x50,
+            KeyboardKey::DownArrow => 0x51,
+            KeyboardKey::UpArrow => 0x52,
+            KeyboardKey::NumLock => 0x53,
+            KeyboardKey::KeypadSlash => 0x54,
+            KeyboardKey::KeypadAsterisk => 0x55,
+            KeyboardKey::KeypadMinus => 0x56,
+            KeyboardKey::KeypadPlus => 0x57,
+            KeyboardKey::KeypadEnter => 0x58,
+            KeyboardKey::Keypad1 => 0x59,
+            KeyboardKey::Keypad2 => 0x5A,
+            KeyboardKey::Keypad3 => 0x5B,
+            KeyboardKey::Keypad4 => 0x5C,
+            KeyboardKey::Keypad5 => 0x5D,
+            KeyboardKey::Keypad6 => 0x5E,
+            KeyboardKey::Keypad7 => 0x5F,
+            KeyboardKey::Keypad8 => 0x60,
+            KeyboardKey::Keypad9 => 0x61,
+            KeyboardKey::Keypad0 => 0x62,
+            KeyboardKey::KeypadPeriod => 0x63,
+            KeyboardKey::NonUsBackslash => 0x64,
+            KeyboardKey::Application => 0x65,
+            KeyboardKey::Mute => 0x7F,
+            KeyboardKey::VolumeUp => 0x80,
+            KeyboardKey::VolumeDown => 0x81,
+            KeyboardKey::International1 => 0x87,
+            KeyboardKey::International2 => 0x88,
+            KeyboardKey::International3 => 0x89,
+            KeyboardKey::International4 => 0x8A,
+            KeyboardKey::International5 => 0x8B,
+            KeyboardKey::International6 => 0x8C,
+            KeyboardKey::International7 => 0x8D,
+            KeyboardKey::International8 => 0x8E,
+            KeyboardKey::International9 => 0x8F,
+            KeyboardKey::Lang1 => 0x90,
+            KeyboardKey::Lang2 => 0x91,
+            KeyboardKey::Lang3 => 0x92,
+            KeyboardKey::Lang4 => 0x93,
+            KeyboardKey::Lang5 => 0x94,
+            KeyboardKey::Lang6 => 0x95,
+            KeyboardKey::Lang7 => 0x96,
+            KeyboardKey::Lang8 => 0x97,
+            KeyboardKey::Lang9 => 0x98,
         };
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum KeyboardModifierKey {
     LeftControl,
     LeftShift,
@@ -118,7 +296,7 @@ pub enum KeyboardModifierKey {
 }
 
 impl KeyboardModifierKey {
-    fn mask(&self) -> u8 {
+    const fn mask(&self) -> u8 {
         return match &self {
             KeyboardModifierKey::LeftControl => 1,
             KeyboardModifierKey::LeftShift => 2,
@@ -132,132 +310,644 @@ impl KeyboardModifierKey {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+bitflags! {
+    /// Set of modifiers, laid out like the modifiers byte of the keyboard report.
+    pub struct Modifiers: u8 {
+        const LEFT_CTRL = 0x01;
+        const LEFT_SHIFT = 0x02;
+        const LEFT_ALT = 0x04;
+        const LEFT_WIN = 0x08;
+        const RIGHT_CTRL = 0x10;
+        const RIGHT_SHIFT = 0x20;
+        const RIGHT_ALT = 0x40;
+        const RIGHT_WIN = 0x80;
+    }
+}
+
+// Aliases of the left modifiers, the ones shortcuts are usually written with. Declared apart
+// from the flags so that debug output names every modifier once.
+impl Modifiers {
+    pub const CTRL: Modifiers = Modifiers::LEFT_CTRL;
+    pub const SHIFT: Modifiers = Modifiers::LEFT_SHIFT;
+    pub const ALT: Modifiers = Modifiers::LEFT_ALT;
+    pub const WIN: Modifiers = Modifiers::LEFT_WIN;
+}
+
+impl From<KeyboardModifierKey> for Modifiers {
+    fn from(modifier: KeyboardModifierKey) -> Self {
+        Modifiers::from_bits_truncate(modifier.mask())
+    }
+}
+
+// Modifiers in the order of their bits in the report, which is also their display order.
+const MODIFIERS: [KeyboardModifierKey; 8] = [
+    KeyboardModifierKey::LeftControl,
+    KeyboardModifierKey::LeftShift,
+    KeyboardModifierKey::LeftAlt,
+    KeyboardModifierKey::LeftWindows,
+    KeyboardModifierKey::RightControl,
+    KeyboardModifierKey::RightShift,
+    KeyboardModifierKey::RightAlt,
+    KeyboardModifierKey::RightWindows,
+];
+
+/// Modifiers and up to six keys pressed together.
+///
+/// Both are sets, the keys being kept in the order of their usage ids, so keys clicks built in
+/// any order or with repeated keys compare equal. A seventh key does not fit, which
+/// [`KeysClick::try_add_key`] reports, chords with more keys are sent with [`Keyboard::send_keys`].
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub struct KeysClick {
-    modifiers: Vec<KeyboardModifierKey>,
-    keys: Vec<KeyboardKey>,
+    modifiers: Modifiers,
+    keys: [Option<KeyboardKey>; KEYBOARD_ROLLOVER],
 }
 
 impl KeysClick {
-    pub fn new(key: KeyboardKey) -> Self {
+    pub const fn new(key: KeyboardKey) -> Self {
         KeysClick::empty().add_key(key)
     }
 
-    pub fn empty() -> Self {
+    pub const fn empty() -> Self {
         KeysClick {
-            modifiers: Vec::new(),
-            keys: Vec::with_capacity(6),
+            modifiers: Modifiers::empty(),
+            keys: [None; KEYBOARD_ROLLOVER],
+        }
+    }
+
+    pub const fn add_modifier(self, modifier: KeyboardModifierKey) -> Self {
+        self.add_modifiers(Modifiers::from_bits_truncate(modifier.mask()))
+    }
+
+    pub const fn add_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = Modifiers::from_bits_truncate(self.modifiers.bits() | modifiers.bits());
+        self
+    }
+
+    /// Adds a key, leaving a full keys click as it is, see [`KeysClick::try_add_key`].
+    pub const fn add_key(mut self, key: KeyboardKey) -> Self {
+        let id = key.id();
+        let mut index = 0;
+
+        while index < KEYBOARD_ROLLOVER {
+            match self.keys[index] {
+                Some(k) if k.id() == id => return self,
+                Some(k) if k.id() < id => index += 1,
+                _ => break,
+            }
+        }
+
+        if self.keys[KEYBOARD_ROLLOVER - 1].is_some() {
+            return self;
+        }
+
+        let mut shifted = KEYBOARD_ROLLOVER - 1;
+
+        while shifted > index {
+            self.keys[shifted] = self.keys[shifted - 1];
+            shifted -= 1;
+        }
+
+        self.keys[index] = Some(key);
+        self
+    }
+
+    /// Adds a key, failing with [`KeyboardError::TooManyKeys`] when six others are there.
+    pub fn try_add_key(self, key: KeyboardKey) -> Result<Self, KeyboardError> {
+        if !self.has_key(key) && self.key_count() == KEYBOARD_ROLLOVER {
+            return Err(KeyboardError::TooManyKeys(KEYBOARD_ROLLOVER + 1));
+        }
+
+        Ok(self.add_key(key))
+    }
+
+    pub const fn remove_modifier(mut self, modifier: KeyboardModifierKey) -> Self {
+        self.modifiers = Modifiers::from_bits_truncate(self.modifiers.bits() & !modifier.mask());
+        self
+    }
+
+    pub const fn remove_key(mut self, key: KeyboardKey) -> Self {
+        let id = key.id();
+        let mut index = 0;
+        let mut removed = false;
+
+        while index < KEYBOARD_ROLLOVER {
+            if let Some(k) = self.keys[index] {
+                removed |= k.id() == id;
+            }
+
+            if removed {
+                self.keys[index] = if index + 1 < KEYBOARD_ROLLOVER {
+                    self.keys[index + 1]
+                } else {
+                    None
+                };
+            }
+
+            index += 1;
+        }
+
+        self
+    }
+
+    // Adds the modifiers and keys of the other keys click which are not pressed yet.
+    fn merge(&self, other: &KeysClick) -> KeysClick {
+        other
+            .keys()
+            .fold(*self, KeysClick::add_key)
+            .add_modifiers(other.modifiers)
+    }
+
+    /// Keys clicks to send, in order, to go from this keys click to the other one without
+    /// forming chords found in neither of them.
+    ///
+    /// Keys are released before the modifiers change and pressed after, as in "ctrl+c" to
+    /// "shift+v" going through "ctrl", "shift" then "shift+v". Nothing is sent for unchanged
+    /// keys and modifiers, and the last keys click is always the other one when anything changes.
+    pub fn transition(&self, other: &KeysClick) -> Vec<KeysClick> {
+        let released = self
+            .keys()
+            .filter(|key| !other.has_key(*key))
+            .fold(*self, KeysClick::remove_key);
+
+        let modified = KeysClick {
+            modifiers: other.modifiers,
+            ..released
+        };
+
+        let mut transition = Vec::new();
+        let mut current = *self;
+
+        for step in [released, modified, *other] {
+            if step != current {
+                transition.push(step);
+                current = step;
+            }
+        }
+
+        transition
+    }
+
+    pub const fn has_modifier(&self, modifier: KeyboardModifierKey) -> bool {
+        self.modifiers.bits() & modifier.mask() != 0
+    }
+
+    pub const fn has_key(&self, key: KeyboardKey) -> bool {
+        let mut index = 0;
+
+        while index < KEYBOARD_ROLLOVER {
+            if let Some(k) = self.keys[index] {
+                if k.id() == key.id() {
+                    return true;
+                }
+            }
+
+            index += 1;
+        }
+
+        false
+    }
+
+    pub const fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = KeyboardKey> + '_ {
+        self.keys.iter().flatten().cloned()
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.keys().count()
+    }
+
+    fn key_ids(&self) -> [u8; KEYBOARD_ROLLOVER] {
+        let mut ids = [0; KEYBOARD_ROLLOVER];
+
+        for (id, key) in ids.iter_mut().zip(self.keys()) {
+            *id = key.id();
+        }
+
+        ids
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ParseKeyErrorReason {
+    UnknownKey,
+    DuplicateModifier,
+    TooManyKeys,
+    EmptySegment,
+    UnexpectedBrace,
+    MissingOpeningBrace,
+    MissingClosingBrace,
+    MissingKeys,
+    MissingDuration,
+    InvalidDuration,
+    InvalidRepeatCount,
+    UnsupportedCommand,
+    UnmappedCharacter,
+    InvalidArgument,
+    MissingClosingQuote,
+}
+
+/// Error of the key, keys click and key sequence parsers, pointing at the offending token.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParseKeyError {
+    token: String,
+    span: Range<usize>,
+    reason: ParseKeyErrorReason,
+    suggestion: Option<&'static str>,
+}
+
+impl ParseKeyError {
+    pub(crate) fn new(token: &str, start: usize, reason: ParseKeyErrorReason) -> Self {
+        ParseKeyError {
+            token: token.to_string(),
+            span: start..start + token.len(),
+            reason,
+            suggestion: None,
         }
     }
 
-    pub fn add_modifier(mut self, modifier: KeyboardModifierKey) -> Self {
-        self.modifiers.push(modifier);
+    pub(crate) fn with_suggestion(mut self, suggestion: Option<&'static str>) -> Self {
+        self.suggestion = suggestion;
         self
     }
 
-    pub fn add_key(mut self, key: KeyboardKey) -> Self {
-        self.keys.push(key);
+    // Moves the span of an error found in a part of a bigger input.
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
         self
     }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Byte range of the token in the parsed input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn reason(&self) -> ParseKeyErrorReason {
+        self.reason
+    }
+
+    /// Closest known key name when the token is not one.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let reason = match self.reason {
+            ParseKeyErrorReason::UnknownKey => "unknown key",
+            ParseKeyErrorReason::DuplicateModifier => "duplicate modifier",
+            ParseKeyErrorReason::TooManyKeys => "too many keys",
+            ParseKeyErrorReason::EmptySegment => "empty key name",
+            ParseKeyErrorReason::UnexpectedBrace => "unexpected brace",
+            ParseKeyErrorReason::MissingOpeningBrace => "missing opening brace",
+            ParseKeyErrorReason::MissingClosingBrace => "missing closing brace",
+            ParseKeyErrorReason::MissingKeys => "missing keys",
+            ParseKeyErrorReason::MissingDuration => "missing duration",
+            ParseKeyErrorReason::InvalidDuration => "invalid duration",
+            ParseKeyErrorReason::InvalidRepeatCount => "invalid repeat count",
+            ParseKeyErrorReason::UnsupportedCommand => "unsupported command",
+            ParseKeyErrorReason::UnmappedCharacter => "character missing from the layout",
+            ParseKeyErrorReason::InvalidArgument => "invalid argument",
+            ParseKeyErrorReason::MissingClosingQuote => "missing closing quote",
+        };
+
+        write!(f, "{}", reason)?;
+
+        if !self.token.is_empty() {
+            write!(f, " \"{}\"", self.token)?;
+        }
+
+        write!(f, " at {}..{}", self.span.start, self.span.end)?;
+
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean \"{}\"?", suggestion)?;
+        }
+
+        Ok(())
+    }
 }
 
+// Names accepted when parsing keys, the first name of every key is the one it is displayed with.
+pub(crate) const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+    ("a", KeyboardKey::A),
+    ("b", KeyboardKey::B),
+    ("c", KeyboardKey::C),
+    ("d", KeyboardKey::D),
+    ("e", KeyboardKey::E),
+    ("f", KeyboardKey::F),
+    ("g", KeyboardKey::G),
+    ("h", KeyboardKey::H),
+    ("i", KeyboardKey::I),
+    ("j", KeyboardKey::J),
+    ("k", KeyboardKey::K),
+    ("l", KeyboardKey::L),
+    ("m", KeyboardKey::M),
+    ("n", KeyboardKey::N),
+    ("o", KeyboardKey::O),
+    ("p", KeyboardKey::P),
+    ("q", KeyboardKey::Q),
+    ("r", KeyboardKey::R),
+    ("s", KeyboardKey::S),
+    ("t", KeyboardKey::T),
+    ("u", KeyboardKey::U),
+    ("v", KeyboardKey::V),
+    ("w", KeyboardKey::W),
+    ("x", KeyboardKey::X),
+    ("y", KeyboardKey::Y),
+    ("z", KeyboardKey::Z),
+    ("1", KeyboardKey::Num1),
+    ("2", KeyboardKey::Num2),
+    ("3", KeyboardKey::Num3),
+    ("4", KeyboardKey::Num4),
+    ("5", KeyboardKey::Num5),
+    ("6", KeyboardKey::Num6),
+    ("7", KeyboardKey::Num7),
+    ("8", KeyboardKey::Num8),
+    ("9", KeyboardKey::Num9),
+    ("0", KeyboardKey::Num0),
+    ("enter", KeyboardKey::Enter),
+    ("return", KeyboardKey::Enter),
+    ("esc", KeyboardKey::Esc),
+    ("escape", KeyboardKey::Esc),
+    ("backspace", KeyboardKey::Del),
+    ("tab", KeyboardKey::Tab),
+    ("space", KeyboardKey::Space),
+    ("spacebar", KeyboardKey::Space),
+    ("minus", KeyboardKey::Minus),
+    ("equal", KeyboardKey::Equal),
+    ("left-bracket", KeyboardKey::LeftBracket),
+    ("right-bracket", KeyboardKey::RightBracket),
+    ("backslash", KeyboardKey::Backslash),
+    ("non-us-hash", KeyboardKey::NonUsHash),
+    ("semicolon", KeyboardKey::Semicolon),
+    ("quote", KeyboardKey::Quote),
+    ("grave", KeyboardKey::Grave),
+    ("comma", KeyboardKey::Comma),
+    ("period", KeyboardKey::Period),
+    ("slash", KeyboardKey::Slash),
+    ("caps-lock", KeyboardKey::CapsLock),
+    ("capslock", KeyboardKey::CapsLock),
+    ("f1", KeyboardKey::F1),
+    ("f2", KeyboardKey::F2),
+    ("f3", KeyboardKey::F3),
+    ("f4", KeyboardKey::F4),
+    ("f5", KeyboardKey::F5),
+    ("f6", KeyboardKey::F6),
+    ("f7", KeyboardKey::F7),
+    ("f8", KeyboardKey::F8),
+    ("f9", KeyboardKey::F9),
+    ("f10", KeyboardKey::F10),
+    ("f11", KeyboardKey::F11),
+    ("f12", KeyboardKey::F12),
+    ("print-screen", KeyboardKey::PrintScreen),
+    ("prtsc", KeyboardKey::PrintScreen),
+    ("scroll-lock", KeyboardKey::ScrollLock),
+    ("pause", KeyboardKey::Pause),
+    ("insert", KeyboardKey::Insert),
+    ("ins", KeyboardKey::Insert),
+    ("home", KeyboardKey::Home),
+    ("page-up", KeyboardKey::PageUp),
+    ("pgup", KeyboardKey::PageUp),
+    ("delete-forward", KeyboardKey::DeleteForward),
+    ("delete", KeyboardKey::DeleteForward),
+    ("end", KeyboardKey::End),
+    ("page-down", KeyboardKey::PageDown),
+    ("pgdn", KeyboardKey::PageDown),
+    ("right-arrow", KeyboardKey::RightArrow),
+    ("right", KeyboardKey::RightArrow),
+    ("left-arrow", KeyboardKey::LeftArrow),
+    ("left", KeyboardKey::LeftArrow),
+    ("down-arrow", KeyboardKey::DownArrow),
+    ("down", KeyboardKey::DownArrow),
+    ("up-arrow", KeyboardKey::UpArrow),
+    ("up", KeyboardKey::UpArrow),
+    ("num-lock", KeyboardKey::NumLock),
+    ("keypad-slash", KeyboardKey::KeypadSlash),
+    ("keypad-asterisk", KeyboardKey::KeypadAsterisk),
+    ("keypad-minus", KeyboardKey::KeypadMinus),
+    ("keypad-plus", KeyboardKey::KeypadPlus),
+    ("plus", KeyboardKey::KeypadPlus),
+    ("keypad-enter", KeyboardKey::KeypadEnter),
+    ("keypad-1", KeyboardKey::Keypad1),
+    ("keypad-2", KeyboardKey::Keypad2),
+    ("keypad-3", KeyboardKey::Keypad3),
+    ("keypad-4", KeyboardKey::Keypad4),
+    ("keypad-5", KeyboardKey::Keypad5),
+    ("keypad-6", KeyboardKey::Keypad6),
+    ("keypad-7", KeyboardKey::Keypad7),
+    ("keypad-8", KeyboardKey::Keypad8),
+    ("keypad-9", KeyboardKey::Keypad9),
+    ("keypad-0", KeyboardKey::Keypad0),
+    ("keypad-period", KeyboardKey::KeypadPeriod),
+    ("non-us-backslash", KeyboardKey::NonUsBackslash),
+    ("application", KeyboardKey::Application),
+    ("menu", KeyboardKey::Application),
+    ("mute", KeyboardKey::Mute),
+    ("volume-mute", KeyboardKey::Mute),
+    ("volume-up", KeyboardKey::VolumeUp),
+    ("volume-down", KeyboardKey::VolumeDown),
+    ("international-1", KeyboardKey::International1),
+    ("ro", KeyboardKey::International1),
+    ("international-2", KeyboardKey::International2),
+    ("katakana-hiragana", KeyboardKey::International2),
+    ("kana", KeyboardKey::International2),
+    ("international-3", KeyboardKey::International3),
+    ("yen", KeyboardKey::International3),
+    ("international-4", KeyboardKey::International4),
+    ("henkan", KeyboardKey::International4),
+    ("international-5", KeyboardKey::International5),
+    ("muhenkan", KeyboardKey::International5),
+    ("international-6", KeyboardKey::International6),
+    ("international-7", KeyboardKey::International7),
+    ("international-8", KeyboardKey::International8),
+    ("international-9", KeyboardKey::International9),
+    ("lang-1", KeyboardKey::Lang1),
+    ("hangul", KeyboardKey::Lang1),
+    ("hangeul", KeyboardKey::Lang1),
+    ("lang-2", KeyboardKey::Lang2),
+    ("hanja", KeyboardKey::Lang2),
+    ("lang-3", KeyboardKey::Lang3),
+    ("katakana", KeyboardKey::Lang3),
+    ("lang-4", KeyboardKey::Lang4),
+    ("hiragana", KeyboardKey::Lang4),
+    ("lang-5", KeyboardKey::Lang5),
+    ("zenkaku-hankaku", KeyboardKey::Lang5),
+    ("lang-6", KeyboardKey::Lang6),
+    ("lang-7", KeyboardKey::Lang7),
+    ("lang-8", KeyboardKey::Lang8),
+    ("lang-9", KeyboardKey::Lang9),
+];
+
+pub(crate) const MODIFIER_NAMES: &[(&str, KeyboardModifierKey)] = &[
+    ("left-ctrl", KeyboardModifierKey::LeftControl),
+    ("ctrl", KeyboardModifierKey::LeftControl),
+    ("control", KeyboardModifierKey::LeftControl),
+    ("left-shift", KeyboardModifierKey::LeftShift),
+    ("shift", KeyboardModifierKey::LeftShift),
+    ("left-alt", KeyboardModifierKey::LeftAlt),
+    ("alt", KeyboardModifierKey::LeftAlt),
+    ("left-win", KeyboardModifierKey::LeftWindows),
+    ("win", KeyboardModifierKey::LeftWindows),
+    ("windows", KeyboardModifierKey::LeftWindows),
+    ("super", KeyboardModifierKey::LeftWindows),
+    ("cmd", KeyboardModifierKey::LeftWindows),
+    ("meta", KeyboardModifierKey::LeftWindows),
+    ("right-ctrl", KeyboardModifierKey::RightControl),
+    ("right-shift", KeyboardModifierKey::RightShift),
+    ("right-alt", KeyboardModifierKey::RightAlt),
+    ("altgr", KeyboardModifierKey::RightAlt),
+    ("right-win", KeyboardModifierKey::RightWindows),
+];
+
 impl TryFrom<&str> for KeyboardKey {
-    type Error = &'static str;
+    type Error = ParseKeyError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.to_lowercase().as_str() {
-            "a" => Ok(KeyboardKey::A),
-            "b" => Ok(KeyboardKey::B),
-            "c" => Ok(KeyboardKey::C),
-            "d" => Ok(KeyboardKey::D),
-            "e" => Ok(KeyboardKey::E),
-            "f" => Ok(KeyboardKey::F),
-            "g" => Ok(KeyboardKey::G),
-            "h" => Ok(KeyboardKey::H),
-            "i" => Ok(KeyboardKey::I),
-            "j" => Ok(KeyboardKey::J),
-            "k" => Ok(KeyboardKey::K),
-            "l" => Ok(KeyboardKey::L),
-            "m" => Ok(KeyboardKey::M),
-            "n" => Ok(KeyboardKey::N),
-            "o" => Ok(KeyboardKey::O),
-            "p" => Ok(KeyboardKey::P),
-            "q" => Ok(KeyboardKey::Q),
-            "r" => Ok(KeyboardKey::R),
-            "s" => Ok(KeyboardKey::S),
-            "t" => Ok(KeyboardKey::T),
-            "u" => Ok(KeyboardKey::U),
-            "v" => Ok(KeyboardKey::V),
-            "w" => Ok(KeyboardKey::W),
-            "x" => Ok(KeyboardKey::X),
-            "y" => Ok(KeyboardKey::Y),
-            "z" => Ok(KeyboardKey::Z),
-            "1" => Ok(KeyboardKey::Num1),
-            "2" => Ok(KeyboardKey::Num2),
-            "3" => Ok(KeyboardKey::Num3),
-            "4" => Ok(KeyboardKey::Num4),
-            "5" => Ok(KeyboardKey::Num5),
-            "6" => Ok(KeyboardKey::Num6),
-            "7" => Ok(KeyboardKey::Num7),
-            "8" => Ok(KeyboardKey::Num8),
-            "9" => Ok(KeyboardKey::Num9),
-            "0" => Ok(KeyboardKey::Num0),
-            "enter" => Ok(KeyboardKey::Enter),
-            "esc" => Ok(KeyboardKey::Esc),
-            "del" => Ok(KeyboardKey::Del),
-            "tab" => Ok(KeyboardKey::Tab),
-            "space" => Ok(KeyboardKey::Space),
-            v if v.is_empty() => Err("Input is empty"),
-            _ => Err("Invalid input"),
-        }
+        find_name(KEY_NAMES, value).ok_or_else(|| unknown_name_error(value, 0))
     }
 }
 
 impl TryFrom<&str> for KeyboardModifierKey {
-    type Error = &'static str;
+    type Error = ParseKeyError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.to_lowercase().as_str() {
-            "left-ctrl" => Ok(KeyboardModifierKey::LeftControl),
-            "left-shift" => Ok(KeyboardModifierKey::LeftShift),
-            "left-alt" => Ok(KeyboardModifierKey::LeftAlt),
-            "left-win" => Ok(KeyboardModifierKey::LeftWindows),
-            "right-ctrl" => Ok(KeyboardModifierKey::RightControl),
-            "right-shift" => Ok(KeyboardModifierKey::RightShift),
-            "right-alt" => Ok(KeyboardModifierKey::RightAlt),
-            "right-win" => Ok(KeyboardModifierKey::RightWindows),
-            v if v.is_empty() => Err("Input is empty"),
-            _ => Err("Invalid input"),
+        find_name(MODIFIER_NAMES, value).ok_or_else(|| unknown_name_error(value, 0))
+    }
+}
+
+fn find_name<T: Copy>(names: &[(&str, T)], value: &str) -> Option<T> {
+    names
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|(_, item)| *item)
+}
+
+fn unknown_name_error(value: &str, start: usize) -> ParseKeyError {
+    if value.is_empty() {
+        return ParseKeyError::new(value, start, ParseKeyErrorReason::EmptySegment);
+    }
+
+    let names = KEY_NAMES
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(MODIFIER_NAMES.iter().map(|(name, _)| *name));
+
+    ParseKeyError::new(value, start, ParseKeyErrorReason::UnknownKey)
+        .with_suggestion(closest_name(value, names))
+}
+
+// Only suggest names a couple of typos away, anything further is likely unrelated.
+pub(crate) fn closest_name(
+    value: &str,
+    names: impl Iterator<Item = &'static str>,
+) -> Option<&'static str> {
+    let value = value.to_lowercase();
+
+    names
+        .map(|name| (edit_distance(&value, &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= 2 && *distance < value.chars().count())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
         }
+
+        previous = current;
     }
+
+    previous[b.len()]
 }
 
+/// Text form of the keys click with neither modifiers nor keys, the released state.
+pub(crate) const EMPTY_KEYS_CLICK: &str = "none";
+
+// Key names are case insensitive and may be surrounded with whitespace. As the plus sign
+// separates keys, the plus key is written either as "plus", as an empty name followed by
+// another plus, as in "ctrl++", or as a trailing plus, as in "ctrl+".
 impl TryFrom<&str> for KeysClick {
-    type Error = &'static str;
+    type Error = ParseKeyError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            return Err("Input is empty");
+        if value.trim().eq_ignore_ascii_case(EMPTY_KEYS_CLICK) {
+            return Ok(KeysClick::empty());
+        }
+
+        // Trimmed segments between plus signs with their offset in the input.
+        let mut split = Vec::new();
+        let mut offset = 0;
+
+        for segment in value.split('+') {
+            let trimmed = segment.trim();
+            let leading = segment.len() - segment.trim_start().len();
+
+            split.push((trimmed, offset + leading));
+            offset += segment.len() + 1;
         }
 
-        let split: Vec<&str> = value.split("+").collect();
         let mut keys_click = KeysClick::empty();
+        let mut index = 0;
 
-        for arg in split {
-            let try_modifier = KeyboardModifierKey::try_from(arg);
-            let try_key = KeyboardKey::try_from(arg);
+        while index < split.len() {
+            let (arg, start) = split[index];
+            index += 1;
 
-            keys_click = match (try_modifier, try_key) {
-                (Ok(modifier), Err(_)) => keys_click.add_modifier(modifier),
-                (Err(_), Ok(key)) => keys_click.add_key(key),
-                _ => return Err("Invalid input"),
-            }
+            let key = if arg.is_empty() {
+                match split.get(index) {
+                    Some(("", _)) if !value.trim().is_empty() => {
+                        index += 1;
+                        KeyboardKey::KeypadPlus
+                    }
+                    None if index > 1 => KeyboardKey::KeypadPlus,
+                    _ => return Err(unknown_name_error(arg, start)),
+                }
+            } else if let Some(modifier) = find_name(MODIFIER_NAMES, arg) {
+                if keys_click.has_modifier(modifier) {
+                    let reason = ParseKeyErrorReason::DuplicateModifier;
+                    return Err(ParseKeyError::new(arg, start, reason));
+                }
+
+                keys_click = keys_click.add_modifier(modifier);
+                continue;
+            } else {
+                find_name(KEY_NAMES, arg).ok_or_else(|| unknown_name_error(arg, start))?
+            };
+
+            keys_click = keys_click.try_add_key(key).map_err(|_| {
+                let token = if arg.is_empty() { "+" } else { arg };
+                ParseKeyError::new(token, start, ParseKeyErrorReason::TooManyKeys)
+            })?;
         }
 
         Ok(keys_click)
     }
 }
 
+impl FromStr for KeysClick {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KeysClick::try_from(s)
+    }
+}
+
 impl Display for KeyboardKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         let value = match self {
@@ -299,9 +989,87 @@ impl Display for KeyboardKey {
             KeyboardKey::Num0 => "0",
             KeyboardKey::Enter => "enter",
             KeyboardKey::Esc => "esc",
-            KeyboardKey::Del => "del",
+            KeyboardKey::Del => "backspace",
             KeyboardKey::Tab => "tab",
             KeyboardKey::Space => "space",
+            KeyboardKey::Minus => "minus",
+            KeyboardKey::Equal => "equal",
+            KeyboardKey::LeftBracket => "left-bracket",
+            KeyboardKey::RightBracket => "right-bracket",
+            KeyboardKey::Backslash => "backslash",
+            KeyboardKey::NonUsHash => "non-us-hash",
+            KeyboardKey::Semicolon => "semicolon",
+            KeyboardKey::Quote => "quote",
+            KeyboardKey::Grave => "grave",
+            KeyboardKey::Comma => "comma",
+            KeyboardKey::Period => "period",
+            KeyboardKey::Slash => "slash",
+            KeyboardKey::CapsLock => "caps-lock",
+            KeyboardKey::F1 => "f1",
+            KeyboardKey::F2 => "f2",
+            KeyboardKey::F3 => "f3",
+            KeyboardKey::F4 => "f4",
+            KeyboardKey::F5 => "f5",
+            KeyboardKey::F6 => "f6",
+            KeyboardKey::F7 => "f7",
+            KeyboardKey::F8 => "f8",
+            KeyboardKey::F9 => "f9",
+            KeyboardKey::F10 => "f10",
+            KeyboardKey::F11 => "f11",
+            KeyboardKey::F12 => "f12",
+            KeyboardKey::PrintScreen => "print-screen",
+            KeyboardKey::ScrollLock => "scroll-lock",
+            KeyboardKey::Pause => "pause",
+            KeyboardKey::Insert => "insert",
+            KeyboardKey::Home => "home",
+            KeyboardKey::PageUp => "page-up",
+            KeyboardKey::DeleteForward => "delete-forward",
+            KeyboardKey::End => "end",
+            KeyboardKey::PageDown => "page-down",
+            KeyboardKey::RightArrow => "right-arrow",
+            KeyboardKey::LeftArrow => "left-arrow",
+            KeyboardKey::DownArrow => "down-arrow",
+            KeyboardKey::UpArrow => "up-arrow",
+            KeyboardKey::NumLock => "num-lock",
+            KeyboardKey::KeypadSlash => "keypad-slash",
+            KeyboardKey::KeypadAsterisk => "keypad-asterisk",
+            KeyboardKey::KeypadMinus => "keypad-minus",
+            KeyboardKey::KeypadPlus => "keypad-plus",
+            KeyboardKey::KeypadEnter => "keypad-enter",
+            KeyboardKey::Keypad1 => "keypad-1",
+            KeyboardKey::Keypad2 => "keypad-2",
+            KeyboardKey::Keypad3 => "keypad-3",
+            KeyboardKey::Keypad4 => "keypad-4",
+            KeyboardKey::Keypad5 => "keypad-5",
+            KeyboardKey::Keypad6 => "keypad-6",
+            KeyboardKey::Keypad7 => "keypad-7",
+            KeyboardKey::Keypad8 => "keypad-8",
+            KeyboardKey::Keypad9 => "keypad-9",
+            KeyboardKey::Keypad0 => "keypad-0",
+            KeyboardKey::KeypadPeriod => "keypad-period",
+            KeyboardKey::NonUsBackslash => "non-us-backslash",
+            KeyboardKey::Application => "application",
+            KeyboardKey::Mute => "mute",
+            KeyboardKey::VolumeUp => "volume-up",
+            KeyboardKey::VolumeDown => "volume-down",
+            KeyboardKey::International1 => "international-1",
+            KeyboardKey::International2 => "international-2",
+            KeyboardKey::International3 => "international-3",
+            KeyboardKey::International4 => "international-4",
+            KeyboardKey::International5 => "international-5",
+            KeyboardKey::International6 => "international-6",
+            KeyboardKey::International7 => "international-7",
+            KeyboardKey::International8 => "international-8",
+            KeyboardKey::International9 => "international-9",
+            KeyboardKey::Lang1 => "lang-1",
+            KeyboardKey::Lang2 => "lang-2",
+            KeyboardKey::Lang3 => "lang-3",
+            KeyboardKey::Lang4 => "lang-4",
+            KeyboardKey::Lang5 => "lang-5",
+            KeyboardKey::Lang6 => "lang-6",
+            KeyboardKey::Lang7 => "lang-7",
+            KeyboardKey::Lang8 => "lang-8",
+            KeyboardKey::Lang9 => "lang-9",
         };
 
         write!(f, "{}", value)
@@ -327,14 +1095,66 @@ impl Display for KeyboardModifierKey {
 
 impl Display for KeysClick {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        let modifiers_string: Vec<String> = self.modifiers.iter().map(|m| m.to_string()).collect();
-        let keys_string: Vec<String> = self.keys.iter().map(|k| k.to_string()).collect();
+        let modifiers_string: Vec<String> = MODIFIERS
+            .iter()
+            .filter(|m| self.has_modifier(**m))
+            .map(|m| m.to_string())
+            .collect();
+        let keys_string: Vec<String> = self.keys().map(|k| k.to_string()).collect();
         let concat = concat(vec![modifiers_string, keys_string]);
 
+        if concat.is_empty() {
+            return write!(f, "{}", EMPTY_KEYS_CLICK);
+        }
+
         write!(f, "{}", join(&concat, "+"))
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum KeyboardError {
+    UnmappedCharacter(char),
+    TooManyKeys(usize),
+    SendFailed,
+}
+
+impl Display for KeyboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            KeyboardError::UnmappedCharacter(c) => {
+                write!(f, "character {:?} is not on the keyboard layout", c)
+            }
+            KeyboardError::TooManyKeys(count) => {
+                write!(f, "{} keys do not fit in a single report", count)
+            }
+            KeyboardError::SendFailed => write!(f, "failed to send the keyboard report"),
+        }
+    }
+}
+
+/// How to send a keys click holding more keys than fit in a single report.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum RolloverPolicy {
+    /// Send nothing and fail with `KeyboardError::TooManyKeys`.
+    Error,
+    /// Send the first six keys and log a warning.
+    Truncate,
+    /// Send the keys six at a time in sequential reports.
+    Split,
+    /// Send the HID ErrorRollOver state, as a physical keyboard does.
+    ErrorRollOver,
+}
+
+/// How `Keyboard::hold` keeps a key down.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum HoldMode {
+    /// Send a single key down report, leaving the auto-repeat to the operating system.
+    Held,
+    /// Release and press the key again after the delay, then at every interval, so that the
+    /// host sees a new key press each time. A zero interval repeats once.
+    Repeat { delay: Duration, interval: Duration },
+}
+
 #[repr(C)]
 #[derive(Debug)]
 struct KeyboardReport {
@@ -346,45 +1166,1851 @@ struct KeyboardReport {
     keys: [u8; 6],
 }
 
+impl KeyboardReport {
+    fn new(modifiers: u8, keys: &[KeyboardKey]) -> Self {
+        let mut ids = [0; KEYBOARD_ROLLOVER];
+
+        for (id, key) in ids.iter_mut().zip(keys.iter()) {
+            *id = key.id();
+        }
+
+        KeyboardReport::with_ids(modifiers, ids)
+    }
+
+    fn with_ids(modifiers: u8, keys: [u8; KEYBOARD_ROLLOVER]) -> Self {
+        KeyboardReport {
+            control_report_id: CONTROL_REPORT_ID,
+            report_length: KEYBOARD_REPORT_SIZE,
+            report_id: KEYBOARD_REPORT_ID,
+            modifiers,
+            _reserved: 0,
+            keys,
+        }
+    }
+
+    fn to_bytes(&self) -> [u8; KEYBOARD_REPORT_SIZE as usize] {
+        let mut bytes = [0; KEYBOARD_REPORT_SIZE as usize];
+
+        bytes[0] = self.control_report_id;
+        bytes[1] = self.report_length;
+        bytes[2] = self.report_id;
+        bytes[3] = self.modifiers;
+        bytes[4] = self._reserved;
+        bytes[5..].copy_from_slice(&self.keys);
+
+        bytes
+    }
+}
+
+// Reports sending the chord under the rollover policy, each with the keys it leaves down.
+fn keyboard_reports(
+    modifiers: Modifiers,
+    keys: &[KeyboardKey],
+    policy: RolloverPolicy,
+) -> Result<Vec<(KeyboardReport, KeysClick)>, KeyboardError> {
+    let report = |keys: &[KeyboardKey]| {
+        let held = keys
+            .iter()
+            .fold(KeysClick::empty().add_modifiers(modifiers), |held, key| {
+                held.add_key(*key)
+            });
+
+        (KeyboardReport::new(modifiers.bits(), keys), held)
+    };
+
+    if keys.len() <= KEYBOARD_ROLLOVER {
+        return Ok(vec![report(keys)]);
+    }
+
+    match policy {
+        RolloverPolicy::Error => Err(KeyboardError::TooManyKeys(keys.len())),
+        RolloverPolicy::Truncate => {
+            warn!(
+                "Chord has {} keys, only the first {} are sent",
+                keys.len(),
+                KEYBOARD_ROLLOVER
+            );
+
+            Ok(vec![report(&keys[..KEYBOARD_ROLLOVER])])
+        }
+        RolloverPolicy::Split => Ok(keys.chunks(KEYBOARD_ROLLOVER).map(report).collect()),
+        // Only the modifiers are known to be down after an error report.
+        RolloverPolicy::ErrorRollOver => Ok(vec![(
+            KeyboardReport::with_ids(modifiers.bits(), [ERROR_ROLL_OVER; KEYBOARD_ROLLOVER]),
+            KeysClick::empty().add_modifiers(modifiers),
+        )]),
+    }
+}
+
 pub struct Keyboard {
-    device: Device,
+    device: Box<dyn Transport>,
+    held: Cell<KeysClick>,
+    rollover_policy: RolloverPolicy,
+    clock: Box<dyn Clock>,
+    hold_duration: Duration,
+    hold_mode: HoldMode,
+    lock_state: Box<dyn LockStateProvider>,
+    // Number of live guards holding each modifier, by bit, whose modifiers are added to every
+    // report.
+    guard_counts: Cell<[u32; 8]>,
+    // Modifiers pressed by guards rather than already held, released once no guard holds them.
+    guard_pressed: Cell<Modifiers>,
 }
 
 impl Keyboard {
+    #[cfg(windows)]
     pub fn init() -> Result<Self, DeviceError> {
         let device = find_device()?;
 
-        Ok(Keyboard { device })
+        Ok(Keyboard::from_transport(device))
+    }
+
+    /// Creates a keyboard sending its reports somewhere else than the vmulti device.
+    pub fn from_transport<T: Transport + 'static>(transport: T) -> Self {
+        Keyboard {
+            device: Box::new(transport),
+            held: Cell::new(KeysClick::empty()),
+            rollover_policy: RolloverPolicy::Truncate,
+            clock: Box::new(SystemClock),
+            hold_duration: Duration::from_millis(0),
+            hold_mode: HoldMode::Held,
+            #[cfg(windows)]
+            lock_state: Box::new(SystemLockState),
+            // The lock keys can only be read on Windows, elsewhere they are taken as off.
+            #[cfg(not(windows))]
+            lock_state: Box::new(LockState::default()),
+            guard_counts: Cell::new([0; 8]),
+            guard_pressed: Cell::new(Modifiers::empty()),
+        }
+    }
+
+    pub fn set_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn set_lock_state_provider<P: LockStateProvider + 'static>(mut self, provider: P) -> Self {
+        self.lock_state = Box::new(provider);
+        self
+    }
+
+    /// How long taps and typed characters keep their keys down.
+    pub fn set_hold_duration(mut self, hold_duration: Duration) -> Self {
+        self.hold_duration = hold_duration;
+        self
+    }
+
+    /// Chooses how `hold` keeps keys down, leaving the repeat to the operating system by default.
+    pub fn set_hold_mode(mut self, hold_mode: HoldMode) -> Self {
+        self.hold_mode = hold_mode;
+        self
+    }
+
+    /// Chooses how keys clicks with more than six keys are sent, truncating by default.
+    pub fn set_rollover_policy(mut self, policy: RolloverPolicy) -> Self {
+        self.rollover_policy = policy;
+        self
+    }
+
+    // Creates a keyboard encoding and timing reports like this one, for another transport and
+    // clock.
+    pub(crate) fn with_transport<T, C>(&self, transport: T, clock: C) -> Keyboard
+    where
+        T: Transport + 'static,
+        C: Clock + 'static,
+    {
+        Keyboard::from_transport(transport)
+            .set_clock(clock)
+            .set_hold_duration(self.hold_duration)
+            .set_hold_mode(self.hold_mode)
+            .set_rollover_policy(self.rollover_policy)
     }
 
+    /// Sends the keys click as is, replacing every held key and modifier but those of the live
+    /// modifier guards.
     pub fn send_click(&self, keys_click: KeysClick) -> bool {
-        let mut keys = [0; 6];
+        self.try_send_click(keys_click).is_ok()
+    }
+
+    pub fn try_send_click(&self, keys_click: KeysClick) -> Result<(), KeyboardError> {
+        let keys_click = keys_click.add_modifiers(self.guarded_modifiers());
+        let report = KeyboardReport::with_ids(keys_click.modifiers.bits(), keys_click.key_ids());
+
+        self.send_report(&report, keys_click)
+    }
 
-        for i in 0..6 {
-            if let Some(key) = keys_click.keys.get(i) {
-                keys[i] = key.id();
+    /// Sends a chord of any number of keys following the rollover policy, replacing every held
+    /// key and modifier.
+    ///
+    /// Keys are kept in the given order, repeated ones being ignored, so truncating keeps the
+    /// first six. The held keys are those the device last received.
+    pub fn send_keys(
+        &self,
+        modifiers: Modifiers,
+        keys: &[KeyboardKey],
+    ) -> Result<(), KeyboardError> {
+        let modifiers = modifiers | self.guarded_modifiers();
+        let mut unique = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            if !unique.contains(key) {
+                unique.push(*key);
             }
         }
 
-        let modifiers = keys_click.modifiers.iter().fold(0, |acc, m| acc | m.mask());
+        for (report, held) in keyboard_reports(modifiers, &unique, self.rollover_policy)? {
+            self.send_report(&report, held)?;
+        }
 
-        let mut report = KeyboardReport {
-            control_report_id: CONTROL_REPORT_ID,
-            report_length: KEYBOARD_REPORT_SIZE,
-            report_id: KEYBOARD_REPORT_ID,
-            modifiers,
-            _reserved: 0,
-            keys,
-        };
+        Ok(())
+    }
+
+    // Sends the report, then records the keys it leaves down.
+    fn send_report(&self, report: &KeyboardReport, held: KeysClick) -> Result<(), KeyboardError> {
+        if !self.device.send_report(&report.to_bytes()) {
+            return Err(KeyboardError::SendFailed);
+        }
 
-        return self
-            .device
-            .send_report(&mut report as *mut _ as *mut c_void);
+        self.held.set(held);
+        Ok(())
     }
-}
 
-impl Drop for Keyboard {
-    fn drop(&mut self) {
-        self.send_click(KeysClick::empty());
+    // Sends the keys click on top of the held keys, going through the rollover policy when they
+    // do not fit in one keys click.
+    fn send_merged(&self, held: KeysClick, keys_click: KeysClick) -> Result<(), KeyboardError> {
+        let added = keys_click.keys().filter(|key| !held.has_key(*key)).count();
+
+        if held.key_count() + added <= KEYBOARD_ROLLOVER {
+            return self.try_send_click(held.merge(&keys_click));
+        }
+
+        let keys: Vec<KeyboardKey> = held.keys().chain(keys_click.keys()).collect();
+
+        self.send_keys(held.modifiers | keys_click.modifiers, &keys)
+    }
+
+    /// Keys and modifiers currently held down.
+    pub fn held(&self) -> KeysClick {
+        self.held.get()
+    }
+
+    /// Goes from the held keys to the keys click with as few reports as possible, releasing
+    /// keys before changing modifiers and pressing keys after, see [`KeysClick::transition`].
+    pub fn transition_to(&self, keys_click: KeysClick) -> Result<(), KeyboardError> {
+        let target = keys_click.add_modifiers(self.guarded_modifiers());
+
+        for step in self.held().transition(&target) {
+            self.try_send_click(step)?;
+        }
+
+        Ok(())
+    }
+
+    /// Presses the key on top of the held ones.
+    pub fn press(&self, key: KeyboardKey) -> bool {
+        let held = self.held();
+
+        if held.has_key(key) {
+            return true;
+        }
+
+        self.send_merged(held, KeysClick::new(key)).is_ok()
+    }
+
+    pub fn release(&self, key: KeyboardKey) -> bool {
+        let held = self.held();
+
+        if !held.has_key(key) {
+            return true;
+        }
+
+        self.send_click(held.remove_key(key))
+    }
+
+    pub fn press_modifier(&self, modifier: KeyboardModifierKey) -> bool {
+        let held = self.held();
+
+        if held.has_modifier(modifier) {
+            return true;
+        }
+
+        self.send_click(held.add_modifier(modifier))
+    }
+
+    pub fn release_modifier(&self, modifier: KeyboardModifierKey) -> bool {
+        let held = self.held();
+
+        if !held.has_modifier(modifier) {
+            return true;
+        }
+
+        self.send_click(held.remove_modifier(modifier))
+    }
+
+    pub fn release_all(&self) -> bool {
+        self.send_click(KeysClick::empty())
+    }
+
+    /// Presses the keys click on top of the held keys, holds it and goes back to the held keys.
+    pub fn tap(&self, keys_click: KeysClick) -> Result<(), KeyboardError> {
+        let held = self.held();
+
+        self.send_merged(held, keys_click)?;
+        self.clock.sleep(self.hold_duration);
+        self.try_send_click(held)
+    }
+
+    /// Presses the key on top of the held keys for the duration, following the hold mode, and
+    /// goes back to the held keys.
+    pub fn hold(&self, key: KeyboardKey, duration: Duration) -> Result<(), KeyboardError> {
+        let held = self.held();
+
+        self.send_merged(held, KeysClick::new(key))?;
+
+        let result = match self.hold_mode {
+            HoldMode::Held => {
+                self.clock.sleep(duration);
+                Ok(())
+            }
+            HoldMode::Repeat { delay, interval } => self.repeat(held, duration, delay, interval),
+        };
+
+        // Release the key even when a repeat fails.
+        self.try_send_click(held)?;
+        result
+    }
+
+    fn repeat(
+        &self,
+        released: KeysClick,
+        duration: Duration,
+        delay: Duration,
+        interval: Duration,
+    ) -> Result<(), KeyboardError> {
+        let pressed = self.held();
+        let mut elapsed = delay.min(duration);
+
+        self.clock.sleep(elapsed);
+
+        while elapsed < duration {
+            self.try_send_click(released)?;
+            self.try_send_click(pressed)?;
+
+            let remaining = duration - elapsed;
+            let pause = if interval == Duration::from_millis(0) {
+                remaining
+            } else {
+                interval.min(remaining)
+            };
+
+            self.clock.sleep(pause);
+            elapsed += pause;
+        }
+
+        Ok(())
+    }
+
+    /// Presses the modifiers until the guard is dropped, keeping them down across keys clicks
+    /// sent in between.
+    ///
+    /// Every requested modifier stays down while the guard lives, even those already held.
+    /// Modifiers pressed by guards go up once no guard holds them, those already held stay.
+    pub fn hold_modifiers(&self, modifiers: Modifiers) -> Result<ModifierGuard<'_>, KeyboardError> {
+        let held = self.held();
+
+        self.count_guard(modifiers, |count| *count += 1);
+        self.guard_pressed
+            .set(self.guard_pressed.get() | (modifiers - held.modifiers));
+
+        let guard = ModifierGuard {
+            keyboard: self,
+            modifiers,
+        };
+
+        self.try_send_click(held)?;
+        Ok(guard)
+    }
+
+    fn guarded_modifiers(&self) -> Modifiers {
+        let counts = self.guard_counts.get();
+        let bits = (0..8)
+            .filter(|bit| counts[*bit] > 0)
+            .fold(0, |bits, bit| bits | 1 << bit);
+
+        Modifiers::from_bits_truncate(bits)
+    }
+
+    fn count_guard<F: Fn(&mut u32)>(&self, modifiers: Modifiers, update: F) {
+        let mut counts = self.guard_counts.get();
+
+        for (bit, count) in counts.iter_mut().enumerate() {
+            if modifiers.bits() & 1 << bit != 0 {
+                update(count);
+            }
+        }
+
+        self.guard_counts.set(counts);
+    }
+
+    /// Runs the closure with the modifiers held, releasing them afterwards even when it fails
+    /// or panics.
+    pub fn with_modifiers<F, T>(&self, modifiers: Modifiers, f: F) -> Result<T, KeyboardError>
+    where
+        F: FnOnce(&Keyboard) -> Result<T, KeyboardError>,
+    {
+        let _guard = self.hold_modifiers(modifiers)?;
+
+        f(self)
+    }
+
+    /// Taps the lock keys whose state differs from the wanted one.
+    pub fn ensure_lock_state(
+        &self,
+        caps_lock: bool,
+        num_lock: bool,
+        scroll_lock: bool,
+    ) -> Result<(), KeyboardError> {
+        let state = self.lock_state.lock_state();
+
+        let locks = [
+            (state.caps_lock != caps_lock, KeyboardKey::CapsLock),
+            (state.num_lock != num_lock, KeyboardKey::NumLock),
+            (state.scroll_lock != scroll_lock, KeyboardKey::ScrollLock),
+        ];
+
+        for (_, key) in locks.iter().filter(|(toggle, _)| *toggle) {
+            self.tap(KeysClick::new(*key))?;
+        }
+
+        Ok(())
+    }
+
+    /// Taps the key switching the input method of the layout on or off. See [`ImeLayout`] for
+    /// the layouts whose keys the stock vmulti descriptor cannot send.
+    pub fn toggle_ime(&self, layout: ImeLayout) -> Result<(), KeyboardError> {
+        self.tap(layout.toggle_keys())
+    }
+
+    pub fn send_sequence(&self, sequence: &KeySequence) -> Result<(), KeyboardError> {
+        for action in sequence.actions() {
+            match action {
+                KeyAction::Click(keys_click) => self.tap(*keys_click)?,
+                KeyAction::Repeat(keys_click, count) => {
+                    for _ in 0..*count {
+                        self.tap(*keys_click)?;
+                    }
+                }
+                KeyAction::Wait(duration) => self.clock.sleep(*duration),
+                KeyAction::Hold(keys_click, inner) => {
+                    let held = self.held();
+
+                    self.send_merged(held, *keys_click)?;
+
+                    // Release the held keys even when the inner actions fail.
+                    let result = self.send_sequence(inner);
+                    self.try_send_click(held)?;
+                    result?;
+                }
+                KeyAction::Press(keys_click) => self.send_merged(self.held(), *keys_click)?,
+                KeyAction::Release(keys_click) => {
+                    let held = self.held();
+                    let released = keys_click
+                        .keys()
+                        .fold(held, |held, key| held.remove_key(key));
+
+                    self.try_send_click(KeysClick {
+                        modifiers: held.modifiers - keys_click.modifiers,
+                        ..released
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Types the text as it would be typed on the given layout, whatever the caps lock state.
+    ///
+    /// Num lock is turned on for Alt codes and back off afterwards.
+    pub fn type_text(
+        &self,
+        layout: &KeyboardLayout,
+        text: &str,
+        options: &TypingOptions,
+    ) -> Result<(), KeyboardError> {
+        let state = self.lock_state.lock_state();
+        let keys_clicks =
+            timed_keys_clicks(layout, text, options, state.caps_lock, self.hold_duration)?;
+
+        // The keypad only types the digits of Alt codes with num lock on.
+        let num_lock = KeysClick::new(KeyboardKey::NumLock);
+        let switch_num_lock = !state.num_lock && uses_alt_numpad(layout, text, options);
+
+        if switch_num_lock {
+            self.tap(num_lock)?;
+        }
+
+        let typed = keys_clicks.into_iter().try_for_each(|(keys_click, pause)| {
+            self.try_send_click(keys_click)?;
+            self.clock.sleep(pause);
+            Ok(())
+        });
+
+        // Num lock goes back off even when typing failed.
+        if switch_num_lock {
+            let restored = self.tap(num_lock);
+            typed?;
+            return restored;
+        }
+
+        typed
+    }
+}
+
+/// Modifiers held by `Keyboard::hold_modifiers`, released on drop.
+pub struct ModifierGuard<'a> {
+    keyboard: &'a Keyboard,
+    modifiers: Modifiers,
+}
+
+impl Drop for ModifierGuard<'_> {
+    fn drop(&mut self) {
+        self.keyboard
+            .count_guard(self.modifiers, |count| *count -= 1);
+
+        let guard_pressed = &self.keyboard.guard_pressed;
+        let released = guard_pressed.get() - self.keyboard.guarded_modifiers();
+
+        if released.is_empty() {
+            return;
+        }
+
+        guard_pressed.set(guard_pressed.get() - released);
+
+        let held = self.keyboard.held();
+
+        self.keyboard.send_click(KeysClick {
+            modifiers: held.modifiers - released,
+            ..held
+        });
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::testing::RecordingClock;
+    use crate::device::testing::{FailingTransport, RecordingTransport};
+    use crate::device::Transport;
+    use crate::human::HumanModel;
+    use crate::keyboard::{
+        HoldMode, Keyboard, KeyboardError, KeyboardKey, KeyboardModifierKey, KeysClick, Modifiers,
+        ParseKeyErrorReason, RolloverPolicy, KEY_NAMES, MODIFIER_NAMES,
+    };
+    use crate::layout::{ImeLayout, KeyboardLayout};
+    use crate::lock::testing::FakeLockState;
+    use crate::lock::LockState;
+    use crate::sequence::KeySequence;
+    use crate::typing::{Fallback, TypingOptions};
+    use proptest::prelude::*;
+    use proptest::sample::subsequence;
+    use std::collections::{HashMap, HashSet};
+    use std::convert::TryFrom;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::time::Duration;
+
+    // Modifiers byte and key ids of every sent report.
+    fn sent(transport: &RecordingTransport) -> Vec<(u8, Vec<u8>)> {
+        transport
+            .reports()
+            .iter()
+            .map(|r| (r[3], r[5..].iter().cloned().filter(|k| *k != 0).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_send_click_report() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.send_click(
+            KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::RightShift),
+        );
+
+        assert_eq!(
+            transport.reports(),
+            vec![vec![0x40, 11, 0x07, 32, 0, 0x04, 0, 0, 0, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn test_media_keys() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.send_click(KeysClick::try_from("volume-up").unwrap());
+        keyboard.send_click(KeysClick::try_from("volume-down+mute").unwrap());
+
+        assert_eq!(
+            sent(&transport),
+            vec![(0, vec![0x80]), (0, vec![0x7F, 0x81])]
+        );
+        assert_eq!(KeyboardKey::try_from("volume-mute"), Ok(KeyboardKey::Mute));
+    }
+
+    #[test]
+    fn test_ime_keys() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.toggle_ime(ImeLayout::Korean).unwrap();
+        keyboard.toggle_ime(ImeLayout::JapaneseOnUs).unwrap();
+        keyboard.toggle_ime(ImeLayout::KoreanRightAlt).unwrap();
+        keyboard.send_click(KeysClick::try_from("henkan+hanja").unwrap());
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0, vec![0x90]),
+                (0, vec![]),
+                (4, vec![0x35]),
+                (0, vec![]),
+                (0x40, vec![]),
+                (0, vec![]),
+                (0, vec![0x8A, 0x91]),
+            ]
+        );
+        assert_eq!(
+            KeyboardKey::try_from("kana"),
+            Ok(KeyboardKey::International2)
+        );
+        assert_eq!(KeyboardKey::Lang5.to_string(), "lang-5");
+    }
+
+    #[test]
+    fn test_key_order_follows_usage_ids() {
+        let mut keys: Vec<KeyboardKey> = KEY_NAMES.iter().map(|(_, key)| *key).collect();
+        keys.sort();
+        keys.dedup();
+
+        for pair in keys.windows(2) {
+            assert!(pair[0].id() < pair[1].id(), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn test_press_release() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.press_modifier(KeyboardModifierKey::LeftShift);
+        keyboard.press(KeyboardKey::A);
+        keyboard.press(KeyboardKey::B);
+        keyboard.release(KeyboardKey::A);
+
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::new(KeyboardKey::B).add_modifier(KeyboardModifierKey::LeftShift)
+        );
+
+        keyboard.release_modifier(KeyboardModifierKey::LeftShift);
+        keyboard.release_all();
+
+        assert_eq!(keyboard.held(), KeysClick::empty());
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (2, vec![]),
+                (2, vec![0x04]),
+                (2, vec![0x04, 0x05]),
+                (2, vec![0x05]),
+                (0, vec![0x05]),
+                (0, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_press_release_unchanged() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.press(KeyboardKey::A);
+        keyboard.press(KeyboardKey::A);
+        keyboard.release(KeyboardKey::B);
+        keyboard.release_modifier(KeyboardModifierKey::LeftAlt);
+
+        assert_eq!(sent(&transport), vec![(0, vec![0x04])]);
+    }
+
+    #[test]
+    fn test_transition_to() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let ctrl_c = KeysClick::new(KeyboardKey::C).add_modifier(KeyboardModifierKey::LeftControl);
+        let shift_v = KeysClick::new(KeyboardKey::V).add_modifier(KeyboardModifierKey::LeftShift);
+
+        keyboard.transition_to(ctrl_c).unwrap();
+        keyboard.transition_to(shift_v).unwrap();
+        keyboard.transition_to(shift_v).unwrap();
+
+        let _guard = keyboard.hold_modifiers(Modifiers::ALT).unwrap();
+        keyboard.transition_to(KeysClick::empty()).unwrap();
+
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::empty().add_modifier(KeyboardModifierKey::LeftAlt)
+        );
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (1, vec![]),
+                (1, vec![0x06]),
+                (1, vec![]),
+                (2, vec![]),
+                (2, vec![0x19]),
+                (6, vec![0x19]),
+                (6, vec![]),
+                (4, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_send_click_replaces_held() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.press(KeyboardKey::A);
+        keyboard.send_click(KeysClick::new(KeyboardKey::C));
+        keyboard.press(KeyboardKey::D);
+
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::new(KeyboardKey::C).add_key(KeyboardKey::D)
+        );
+    }
+
+    #[test]
+    fn test_drop_releases_all() {
+        let transport = RecordingTransport::default();
+
+        {
+            let keyboard = Keyboard::from_transport(transport.clone());
+            keyboard.press(KeyboardKey::A);
+        }
+
+        assert_eq!(sent(&transport), vec![(0, vec![0x04]), (0, vec![])]);
+    }
+
+    const EIGHT_KEYS: [KeyboardKey; 8] = [
+        KeyboardKey::A,
+        KeyboardKey::B,
+        KeyboardKey::C,
+        KeyboardKey::D,
+        KeyboardKey::E,
+        KeyboardKey::F,
+        KeyboardKey::G,
+        KeyboardKey::H,
+    ];
+
+    #[test]
+    fn test_rollover_error() {
+        let transport = RecordingTransport::default();
+        let keyboard =
+            Keyboard::from_transport(transport.clone()).set_rollover_policy(RolloverPolicy::Error);
+
+        assert_eq!(
+            keyboard.send_keys(Modifiers::LEFT_CTRL, &EIGHT_KEYS),
+            Err(KeyboardError::TooManyKeys(8))
+        );
+        assert_eq!(keyboard.held(), KeysClick::empty());
+        assert!(transport.reports().is_empty());
+    }
+
+    #[test]
+    fn test_rollover_truncate() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_rollover_policy(RolloverPolicy::Truncate);
+
+        let first_six = EIGHT_KEYS[..6]
+            .iter()
+            .fold(KeysClick::empty(), |keys_click, key| {
+                keys_click.add_key(*key)
+            })
+            .add_modifiers(Modifiers::LEFT_CTRL);
+
+        assert!(keyboard
+            .send_keys(Modifiers::LEFT_CTRL, &EIGHT_KEYS)
+            .is_ok());
+        assert_eq!(keyboard.held(), first_six);
+        assert_eq!(
+            sent(&transport),
+            vec![(1, vec![0x04, 0x05, 0x06, 0x07, 0x08, 0x09])]
+        );
+    }
+
+    #[test]
+    fn test_rollover_truncate_keeps_given_order() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_rollover_policy(RolloverPolicy::Truncate);
+
+        let mut keys = EIGHT_KEYS.to_vec();
+        keys.reverse();
+        keys.push(KeyboardKey::H);
+
+        let last_six = EIGHT_KEYS[2..]
+            .iter()
+            .fold(KeysClick::empty(), |keys_click, key| {
+                keys_click.add_key(*key)
+            });
+
+        keyboard.send_keys(Modifiers::empty(), &keys).unwrap();
+
+        assert_eq!(keyboard.held(), last_six);
+        assert_eq!(
+            sent(&transport),
+            vec![(0, vec![0x0B, 0x0A, 0x09, 0x08, 0x07, 0x06])]
+        );
+    }
+
+    #[test]
+    fn test_rollover_split() {
+        let transport = RecordingTransport::default();
+        let keyboard =
+            Keyboard::from_transport(transport.clone()).set_rollover_policy(RolloverPolicy::Split);
+
+        assert!(keyboard
+            .send_keys(Modifiers::LEFT_CTRL, &EIGHT_KEYS)
+            .is_ok());
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (1, vec![0x04, 0x05, 0x06, 0x07, 0x08, 0x09]),
+                (1, vec![0x0A, 0x0B]),
+            ]
+        );
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::new(KeyboardKey::G)
+                .add_key(KeyboardKey::H)
+                .add_modifiers(Modifiers::LEFT_CTRL)
+        );
+    }
+
+    #[test]
+    fn test_rollover_error_roll_over() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_rollover_policy(RolloverPolicy::ErrorRollOver);
+
+        assert!(keyboard
+            .send_keys(Modifiers::LEFT_CTRL, &EIGHT_KEYS)
+            .is_ok());
+        assert_eq!(sent(&transport), vec![(1, vec![0x01; 6])]);
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::empty().add_modifiers(Modifiers::LEFT_CTRL)
+        );
+    }
+
+    #[test]
+    fn test_send_failure_keeps_held() {
+        let keyboard = Keyboard::from_transport(FailingTransport);
+
+        assert_eq!(
+            keyboard.send_keys(Modifiers::LEFT_CTRL, &EIGHT_KEYS[..2]),
+            Err(KeyboardError::SendFailed)
+        );
+        assert_eq!(
+            keyboard.try_send_click(KeysClick::new(KeyboardKey::A)),
+            Err(KeyboardError::SendFailed)
+        );
+        assert_eq!(keyboard.held(), KeysClick::empty());
+    }
+
+    #[test]
+    fn test_rollover_press() {
+        let transport = RecordingTransport::default();
+        let keyboard =
+            Keyboard::from_transport(transport.clone()).set_rollover_policy(RolloverPolicy::Error);
+
+        for key in [KeyboardKey::A, KeyboardKey::B, KeyboardKey::C].iter() {
+            keyboard.press(*key);
+        }
+
+        for key in [KeyboardKey::D, KeyboardKey::E, KeyboardKey::F].iter() {
+            assert!(keyboard.press(*key));
+        }
+
+        assert!(!keyboard.press(KeyboardKey::G));
+        assert_eq!(keyboard.held().key_count(), 6);
+        assert_eq!(transport.reports().len(), 6);
+    }
+
+    #[test]
+    fn test_tap() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_duration(Duration::from_millis(30));
+
+        keyboard.press_modifier(KeyboardModifierKey::LeftShift);
+        keyboard
+            .tap(KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::LeftShift))
+            .unwrap();
+
+        assert_eq!(
+            keyboard.held(),
+            KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift)
+        );
+        assert_eq!(
+            sent(&transport),
+            vec![(2, vec![]), (2, vec![0x04]), (2, vec![])]
+        );
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(30)]);
+    }
+
+    #[test]
+    fn test_type_text_timing() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_duration(Duration::from_millis(10))
+            .set_lock_state_provider(FakeLockState::default());
+
+        let options = TypingOptions::new().set_delay(Duration::from_millis(50));
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "ab", &options)
+            .unwrap();
+
+        assert_eq!(transport.reports().len(), 4);
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+                Duration::from_millis(50),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_text_jitter_is_seeded() {
+        let type_text = || {
+            let clock = RecordingClock::default();
+            let keyboard = Keyboard::from_transport(RecordingTransport::default())
+                .set_clock(clock.clone())
+                .set_lock_state_provider(FakeLockState::default());
+
+            let options = TypingOptions::new()
+                .set_delay(Duration::from_millis(50))
+                .set_jitter(Duration::from_millis(25))
+                .set_seed(42);
+
+            keyboard
+                .type_text(&KeyboardLayout::us(), "hello", &options)
+                .unwrap();
+
+            clock.sleeps()
+        };
+
+        let sleeps = type_text();
+
+        assert_eq!(sleeps, type_text());
+        assert!(sleeps
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|d| *d >= Duration::from_millis(25) && *d <= Duration::from_millis(75)));
+    }
+
+    #[test]
+    fn test_send_sequence() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone()).set_clock(clock.clone());
+
+        let sequence =
+            KeySequence::try_from("left-ctrl+c, wait 200ms, tab x2, hold left-shift { a b }")
+                .unwrap();
+
+        keyboard.send_sequence(&sequence).unwrap();
+
+        assert_eq!(keyboard.held(), KeysClick::empty());
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (1, vec![0x06]),
+                (0, vec![]),
+                (0, vec![0x2B]),
+                (0, vec![]),
+                (0, vec![0x2B]),
+                (0, vec![]),
+                (2, vec![]),
+                (2, vec![0x04]),
+                (2, vec![]),
+                (2, vec![0x05]),
+                (2, vec![]),
+                (0, vec![]),
+            ]
+        );
+        assert!(clock.sleeps().contains(&Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_send_sequence_releases_hold_on_error() {
+        let transport = RecordingTransport::default();
+        let keyboard =
+            Keyboard::from_transport(transport.clone()).set_rollover_policy(RolloverPolicy::Error);
+
+        let sequence = KeySequence::try_from("hold a+b+c+d+e+f { g }").unwrap();
+
+        assert_eq!(
+            keyboard.send_sequence(&sequence),
+            Err(KeyboardError::TooManyKeys(7))
+        );
+        assert_eq!(keyboard.held(), KeysClick::empty());
+        assert_eq!(sent(&transport).last(), Some(&(0, vec![])));
+    }
+
+    #[test]
+    fn test_send_sequence_press_release() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let sequence =
+            KeySequence::try_from("press left-alt, tab, press a, release left-alt, release a")
+                .unwrap();
+
+        keyboard.send_sequence(&sequence).unwrap();
+
+        assert_eq!(keyboard.held(), KeysClick::empty());
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (4, vec![]),
+                (4, vec![0x2B]),
+                (4, vec![]),
+                (4, vec![0x04]),
+                (0, vec![0x04]),
+                (0, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keys_click_canonical() {
+        let ctrl_shift_a = KeysClick::new(KeyboardKey::A)
+            .add_modifier(KeyboardModifierKey::LeftControl)
+            .add_modifier(KeyboardModifierKey::LeftShift);
+
+        let reordered = KeysClick::empty()
+            .add_modifier(KeyboardModifierKey::LeftShift)
+            .add_key(KeyboardKey::A)
+            .add_modifier(KeyboardModifierKey::LeftControl)
+            .add_modifier(KeyboardModifierKey::LeftShift)
+            .add_key(KeyboardKey::A);
+
+        assert_eq!(reordered, ctrl_shift_a);
+        assert_eq!(reordered.to_string(), "left-ctrl+left-shift+a");
+        assert_eq!(
+            KeysClick::new(KeyboardKey::C)
+                .add_key(KeyboardKey::A)
+                .add_key(KeyboardKey::B)
+                .to_string(),
+            "a+b+c"
+        );
+        assert_eq!("b+a+a".parse::<KeysClick>(), "a+b".parse::<KeysClick>());
+
+        let mut set = HashSet::new();
+        set.insert(ctrl_shift_a);
+        set.insert(reordered);
+
+        assert_eq!(set.len(), 1);
+    }
+
+    const CTRL_SHIFT_T: KeysClick = KeysClick::new(KeyboardKey::T)
+        .add_modifier(KeyboardModifierKey::LeftControl)
+        .add_modifier(KeyboardModifierKey::LeftShift);
+
+    #[test]
+    fn test_keys_click_const() {
+        assert_eq!(CTRL_SHIFT_T.to_string(), "left-ctrl+left-shift+t");
+        assert_eq!(
+            CTRL_SHIFT_T.modifiers(),
+            Modifiers::LEFT_CTRL | Modifiers::LEFT_SHIFT
+        );
+        assert_eq!(
+            CTRL_SHIFT_T.keys().collect::<Vec<_>>(),
+            vec![KeyboardKey::T]
+        );
+    }
+
+    #[test]
+    fn test_keys_click_full() {
+        let full = EIGHT_KEYS
+            .iter()
+            .fold(KeysClick::empty(), |keys_click, key| {
+                keys_click.add_key(*key)
+            });
+
+        assert_eq!(full.key_count(), 6);
+        assert!(!full.has_key(KeyboardKey::G));
+        assert_eq!(
+            full.remove_key(KeyboardKey::C)
+                .add_key(KeyboardKey::H)
+                .to_string(),
+            "a+b+d+e+f+h"
+        );
+        assert_eq!(full.remove_key(KeyboardKey::Z), full);
+        assert_eq!(
+            full.try_add_key(KeyboardKey::G),
+            Err(KeyboardError::TooManyKeys(7))
+        );
+        assert_eq!(full.try_add_key(KeyboardKey::A), Ok(full));
+        assert_eq!(
+            KeysClick::new(KeyboardKey::A).try_add_key(KeyboardKey::B),
+            Ok(KeysClick::new(KeyboardKey::A).add_key(KeyboardKey::B))
+        );
+    }
+
+    #[test]
+    fn test_keys_click_transition() {
+        let click = |value: &str| KeysClick::try_from(value).unwrap();
+
+        assert_eq!(
+            click("ctrl+c").transition(&click("shift+v")),
+            vec![click("ctrl"), click("shift"), click("shift+v")]
+        );
+        assert_eq!(
+            click("ctrl+a+b").transition(&click("ctrl+shift+b+c")),
+            vec![
+                click("ctrl+b"),
+                click("ctrl+shift+b"),
+                click("ctrl+shift+b+c")
+            ]
+        );
+        assert_eq!(click("shift+a").transition(&click("a")), vec![click("a")]);
+        assert_eq!(click("a+b").transition(&click("a")), vec![click("a")]);
+        assert_eq!(
+            KeysClick::empty().transition(&click("alt+tab")),
+            vec![click("alt"), click("alt+tab")]
+        );
+        assert_eq!(click("alt+tab").transition(&click("alt+tab")), vec![]);
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let ctrl_shift = Modifiers::from(KeyboardModifierKey::LeftControl) | Modifiers::LEFT_SHIFT;
+
+        assert_eq!(ctrl_shift.bits(), 0x03);
+        assert!(ctrl_shift.contains(Modifiers::LEFT_SHIFT));
+        assert_eq!(ctrl_shift - Modifiers::LEFT_CTRL, Modifiers::LEFT_SHIFT);
+        assert_eq!(ctrl_shift & Modifiers::RIGHT_ALT, Modifiers::empty());
+        assert_eq!(ctrl_shift, Modifiers::CTRL | Modifiers::SHIFT);
+        assert_eq!(
+            Modifiers::ALT | Modifiers::WIN,
+            Modifiers::from_bits_truncate(0x0C)
+        );
+        assert_eq!(format!("{:?}", Modifiers::CTRL), "LEFT_CTRL");
+        assert_eq!(
+            KeysClick::empty()
+                .add_modifiers(ctrl_shift)
+                .remove_modifier(KeyboardModifierKey::LeftShift)
+                .modifiers(),
+            Modifiers::LEFT_CTRL
+        );
+    }
+
+    fn keys_click_strategy() -> impl Strategy<Value = KeysClick> {
+        let mut keys: Vec<KeyboardKey> = KEY_NAMES.iter().map(|(_, key)| *key).collect();
+        keys.sort();
+        keys.dedup();
+
+        (any::<u8>(), subsequence(keys, 0..=6)).prop_map(|(modifiers, keys)| {
+            keys.into_iter()
+                .fold(KeysClick::empty(), KeysClick::add_key)
+                .add_modifiers(Modifiers::from_bits_truncate(modifiers))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_keys_click_display_parse(keys_click in keys_click_strategy()) {
+            prop_assert_eq!(keys_click.to_string().parse::<KeysClick>(), Ok(keys_click));
+        }
+    }
+
+    #[test]
+    fn test_keys_click_parse() {
+        assert_eq!(
+            KeysClick::try_from("left-ctrl+right-shift+esc"),
+            Ok(KeysClick::new(KeyboardKey::Esc)
+                .add_modifier(KeyboardModifierKey::LeftControl)
+                .add_modifier(KeyboardModifierKey::RightShift))
+        );
+        assert_eq!(
+            KeysClick::try_from("").unwrap_err().reason(),
+            ParseKeyErrorReason::EmptySegment
+        );
+        assert_eq!(KeysClick::try_from("+a").unwrap_err().span(), 0..0);
+        assert_eq!(KeysClick::try_from("ctrl+ +q").unwrap_err().span(), 6..6);
+    }
+
+    #[test]
+    fn test_keys_click_parse_aliases() {
+        let ctrl_shift_esc = KeysClick::new(KeyboardKey::Esc)
+            .add_modifier(KeyboardModifierKey::LeftControl)
+            .add_modifier(KeyboardModifierKey::LeftShift);
+
+        assert_eq!(KeysClick::try_from("Ctrl+Shift+Esc"), Ok(ctrl_shift_esc));
+        assert_eq!(
+            KeysClick::try_from("CONTROL+shift+Escape"),
+            Ok(ctrl_shift_esc)
+        );
+
+        for alias in ["win", "super", "cmd", "meta"].iter() {
+            assert_eq!(
+                KeyboardModifierKey::try_from(*alias),
+                Ok(KeyboardModifierKey::LeftWindows)
+            );
+        }
+
+        assert_eq!(
+            KeyboardModifierKey::try_from("alt"),
+            Ok(KeyboardModifierKey::LeftAlt)
+        );
+        assert_eq!(KeyboardKey::try_from("Return"), Ok(KeyboardKey::Enter));
+        assert_eq!(KeyboardKey::try_from("SpaceBar"), Ok(KeyboardKey::Space));
+        assert_eq!(KeyboardKey::try_from("backspace"), Ok(KeyboardKey::Del));
+        assert_eq!(
+            KeyboardKey::try_from("delete"),
+            Ok(KeyboardKey::DeleteForward)
+        );
+        assert!(KeyboardKey::try_from("del").is_err());
+    }
+
+    #[test]
+    fn test_keys_click_parse_whitespace() {
+        let ctrl_c = KeysClick::new(KeyboardKey::C).add_modifier(KeyboardModifierKey::LeftControl);
+
+        assert_eq!(KeysClick::try_from("ctrl + c"), Ok(ctrl_c));
+        assert_eq!(KeysClick::try_from("  ctrl+c\t"), Ok(ctrl_c));
+        assert_eq!(
+            KeysClick::try_from("   ").unwrap_err().reason(),
+            ParseKeyErrorReason::EmptySegment
+        );
+    }
+
+    #[test]
+    fn test_keys_click_parse_plus() {
+        let plus = KeysClick::new(KeyboardKey::KeypadPlus);
+        let ctrl_plus = plus.add_modifier(KeyboardModifierKey::LeftControl);
+
+        assert_eq!(KeysClick::try_from("+"), Ok(plus));
+        assert_eq!(KeysClick::try_from("plus"), Ok(plus));
+        assert_eq!(KeysClick::try_from("ctrl++"), Ok(ctrl_plus));
+        assert_eq!(KeysClick::try_from("ctrl + +"), Ok(ctrl_plus));
+        assert_eq!(KeysClick::try_from("ctrl+plus"), Ok(ctrl_plus));
+        assert_eq!(KeysClick::try_from("ctrl+"), Ok(ctrl_plus));
+        assert_eq!(KeysClick::try_from("ctrl +"), Ok(ctrl_plus));
+        assert_eq!(KeysClick::try_from("a +"), Ok(plus.add_key(KeyboardKey::A)));
+        assert_eq!(
+            KeysClick::try_from("ctrl+++a"),
+            Ok(ctrl_plus.add_key(KeyboardKey::A))
+        );
+        assert_eq!(KeysClick::try_from("ctrl++a").unwrap_err().span(), 5..5);
+    }
+
+    #[test]
+    fn test_keys_click_parse_error() {
+        let error = KeysClick::try_from("ctrll+c").unwrap_err();
+
+        assert_eq!(error.token(), "ctrll");
+        assert_eq!(error.span(), 0..5);
+        assert_eq!(error.reason(), ParseKeyErrorReason::UnknownKey);
+        assert_eq!(error.suggestion(), Some("ctrl"));
+        assert_eq!(
+            error.to_string(),
+            "unknown key \"ctrll\" at 0..5, did you mean \"ctrl\"?"
+        );
+
+        let error = KeysClick::try_from("shift + entr").unwrap_err();
+
+        assert_eq!(error.span(), 8..12);
+        assert_eq!(error.suggestion(), Some("enter"));
+
+        let error = KeysClick::try_from("ctrl+shift+ctrl+a").unwrap_err();
+
+        assert_eq!(error.token(), "ctrl");
+        assert_eq!(error.span(), 11..15);
+        assert_eq!(error.reason(), ParseKeyErrorReason::DuplicateModifier);
+
+        let error = KeysClick::try_from("a+b+c+d+e+f+g").unwrap_err();
+
+        assert_eq!(error.token(), "g");
+        assert_eq!(error.span(), 12..13);
+        assert_eq!(error.reason(), ParseKeyErrorReason::TooManyKeys);
+
+        let error = KeysClick::try_from("qwertyuiop").unwrap_err();
+
+        assert_eq!(error.reason(), ParseKeyErrorReason::UnknownKey);
+        assert_eq!(error.suggestion(), None);
+    }
+
+    #[test]
+    fn test_key_names_match_display() {
+        let mut displayed = Vec::new();
+
+        for (name, key) in KEY_NAMES {
+            if !displayed.contains(key) {
+                assert_eq!(key.to_string(), *name);
+                displayed.push(*key);
+            }
+        }
+
+        let mut displayed = Vec::new();
+
+        for (name, modifier) in MODIFIER_NAMES {
+            if !displayed.contains(modifier) {
+                assert_eq!(modifier.to_string(), *name);
+                displayed.push(*modifier);
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_text_with_caps_lock() {
+        let transport = RecordingTransport::default();
+        let lock_state = FakeLockState::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(RecordingClock::default())
+            .set_lock_state_provider(lock_state.clone());
+
+        lock_state.set(LockState {
+            caps_lock: true,
+            ..Default::default()
+        });
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "aB1", &TypingOptions::new())
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x02, vec![0x04]),
+                (0x00, vec![]),
+                (0x00, vec![0x05]),
+                (0x00, vec![]),
+                (0x00, vec![0x1E]),
+                (0x00, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_text_alt_numpad_turns_num_lock_on() {
+        let transport = RecordingTransport::default();
+        let lock_state = FakeLockState::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(RecordingClock::default())
+            .set_lock_state_provider(lock_state.clone());
+
+        let options = TypingOptions::new().set_fallback(Fallback::AltNumpad);
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "a", &options)
+            .unwrap();
+        keyboard
+            .type_text(&KeyboardLayout::us(), "é", &options)
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport)[..6],
+            [
+                (0, vec![0x04]),
+                (0, vec![]),
+                (0, vec![0x53]),
+                (0, vec![]),
+                (4, vec![]),
+                (4, vec![0x62]),
+            ]
+        );
+
+        lock_state.set(LockState {
+            num_lock: true,
+            ..Default::default()
+        });
+
+        let sent_before = sent(&transport).len();
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "é", &options)
+            .unwrap();
+
+        assert_eq!(sent(&transport)[sent_before], (4, vec![]));
+    }
+
+    #[test]
+    fn test_type_text_alt_numpad_restores_num_lock() {
+        // Records every report, failing the one at the given index.
+        #[derive(Clone)]
+        struct FailingAt(RecordingTransport, usize);
+
+        impl Transport for FailingAt {
+            fn send_report(&self, report: &[u8]) -> bool {
+                let index = self.0.reports().len();
+                self.0.send_report(report) && index != self.1
+            }
+        }
+
+        let options = TypingOptions::new().set_fallback(Fallback::AltNumpad);
+        let num_lock = [(0, vec![0x53]), (0, vec![])];
+
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(RecordingClock::default())
+            .set_lock_state_provider(FakeLockState::default());
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "é", &options)
+            .unwrap();
+
+        let reports = sent(&transport);
+        assert_eq!(reports[..2], num_lock);
+        assert_eq!(reports[reports.len() - 2..], num_lock);
+
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(FailingAt(transport.clone(), 3))
+            .set_clock(RecordingClock::default())
+            .set_lock_state_provider(FakeLockState::default());
+
+        assert_eq!(
+            keyboard.type_text(&KeyboardLayout::us(), "é", &options),
+            Err(KeyboardError::SendFailed)
+        );
+
+        // Alt stays down as the failed report left it, around the num lock tap.
+        let reports = sent(&transport);
+        assert_eq!(reports[reports.len() - 2..], [(4, vec![0x53]), (4, vec![])]);
+    }
+
+    #[test]
+    fn test_ensure_lock_state() {
+        let transport = RecordingTransport::default();
+        let lock_state = FakeLockState::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(RecordingClock::default())
+            .set_lock_state_provider(lock_state.clone());
+
+        lock_state.set(LockState {
+            caps_lock: true,
+            num_lock: false,
+            scroll_lock: false,
+        });
+
+        keyboard.ensure_lock_state(false, true, false).unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x00, vec![0x39]),
+                (0x00, vec![]),
+                (0x00, vec![0x53]),
+                (0x00, vec![]),
+            ]
+        );
+
+        lock_state.set(LockState {
+            caps_lock: false,
+            num_lock: true,
+            scroll_lock: false,
+        });
+
+        keyboard.ensure_lock_state(false, true, false).unwrap();
+
+        assert_eq!(transport.reports().len(), 4);
+    }
+
+    #[test]
+    fn test_hold() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone()).set_clock(clock.clone());
+
+        keyboard.press_modifier(KeyboardModifierKey::LeftShift);
+        keyboard
+            .hold(KeyboardKey::A, Duration::from_millis(750))
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![(0x02, vec![]), (0x02, vec![0x04]), (0x02, vec![])]
+        );
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(750)]);
+    }
+
+    #[test]
+    fn test_hold_repeat() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_mode(HoldMode::Repeat {
+                delay: Duration::from_millis(500),
+                interval: Duration::from_millis(100),
+            });
+
+        keyboard
+            .hold(KeyboardKey::A, Duration::from_millis(750))
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x00, vec![0x04]),
+                (0x00, vec![]),
+                (0x00, vec![0x04]),
+                (0x00, vec![]),
+                (0x00, vec![0x04]),
+                (0x00, vec![]),
+                (0x00, vec![0x04]),
+                (0x00, vec![]),
+            ]
+        );
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_millis(500),
+                Duration::from_millis(100),
+                Duration::from_millis(100),
+                Duration::from_millis(50),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hold_repeat_shorter_than_delay() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_mode(HoldMode::Repeat {
+                delay: Duration::from_millis(500),
+                interval: Duration::from_millis(0),
+            });
+
+        keyboard
+            .hold(KeyboardKey::A, Duration::from_millis(300))
+            .unwrap();
+
+        assert_eq!(transport.reports().len(), 2);
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(300)]);
+
+        keyboard
+            .hold(KeyboardKey::A, Duration::from_millis(800))
+            .unwrap();
+
+        assert_eq!(transport.reports().len(), 6);
+        assert_eq!(
+            clock.sleeps()[1..],
+            [Duration::from_millis(500), Duration::from_millis(300)]
+        );
+    }
+
+    #[test]
+    fn test_with_modifiers() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let result = keyboard.with_modifiers(Modifiers::CTRL | Modifiers::SHIFT, |k| {
+            k.try_send_click(KeysClick::new(KeyboardKey::A))?;
+            k.try_send_click(KeysClick::new(KeyboardKey::B))?;
+            k.release_all();
+
+            Ok(k.held())
+        });
+
+        assert_eq!(
+            result.unwrap().modifiers(),
+            Modifiers::CTRL | Modifiers::SHIFT
+        );
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x03, vec![]),
+                (0x03, vec![0x04]),
+                (0x03, vec![0x05]),
+                (0x03, vec![]),
+                (0x00, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_modifiers_releases_on_error() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let result: Result<(), KeyboardError> = keyboard.with_modifiers(Modifiers::ALT, |k| {
+            k.press(KeyboardKey::Tab);
+            Err(KeyboardError::SendFailed)
+        });
+
+        assert_eq!(result, Err(KeyboardError::SendFailed));
+        assert_eq!(
+            sent(&transport),
+            vec![(0x04, vec![]), (0x04, vec![0x2B]), (0x00, vec![0x2B])]
+        );
+    }
+
+    #[test]
+    fn test_modifier_guard_releases_on_panic() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _guard = keyboard.hold_modifiers(Modifiers::WIN).unwrap();
+            panic!("failed while holding");
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(sent(&transport), vec![(0x08, vec![]), (0x00, vec![])]);
+        assert_eq!(keyboard.held(), KeysClick::empty());
+    }
+
+    #[test]
+    fn test_with_modifiers_already_held() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.press_modifier(KeyboardModifierKey::LeftControl);
+
+        keyboard
+            .with_modifiers(Modifiers::CTRL, |k| {
+                k.try_send_click(KeysClick::new(KeyboardKey::A))?;
+                k.try_send_click(KeysClick::empty())
+            })
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x01, vec![]),
+                (0x01, vec![]),
+                (0x01, vec![0x04]),
+                (0x01, vec![]),
+            ]
+        );
+        assert_eq!(keyboard.held().modifiers(), Modifiers::CTRL);
+    }
+
+    #[test]
+    fn test_modifier_guards_dropped_out_of_order() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        let first = keyboard.hold_modifiers(Modifiers::SHIFT).unwrap();
+        let second = keyboard.hold_modifiers(Modifiers::SHIFT).unwrap();
+
+        drop(first);
+        keyboard.send_click(KeysClick::new(KeyboardKey::A));
+
+        assert_eq!(keyboard.held().modifiers(), Modifiers::SHIFT);
+
+        drop(second);
+
+        assert_eq!(keyboard.held().modifiers(), Modifiers::empty());
+    }
+
+    #[test]
+    fn test_modifier_guard_keeps_held_modifiers() {
+        let transport = RecordingTransport::default();
+        let keyboard = Keyboard::from_transport(transport.clone());
+
+        keyboard.press_modifier(KeyboardModifierKey::LeftControl);
+
+        {
+            let _outer = keyboard
+                .hold_modifiers(Modifiers::CTRL | Modifiers::SHIFT)
+                .unwrap();
+
+            {
+                let _inner = keyboard
+                    .hold_modifiers(Modifiers::SHIFT | Modifiers::ALT)
+                    .unwrap();
+            }
+
+            assert_eq!(
+                keyboard.held().modifiers(),
+                Modifiers::CTRL | Modifiers::SHIFT
+            );
+        }
+
+        assert_eq!(keyboard.held().modifiers(), Modifiers::CTRL);
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x01, vec![]),
+                (0x03, vec![]),
+                (0x07, vec![]),
+                (0x03, vec![]),
+                (0x01, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_text_like_human() {
+        let type_text = |typo_rate| {
+            let transport = RecordingTransport::default();
+            let clock = RecordingClock::default();
+            let keyboard = Keyboard::from_transport(transport.clone())
+                .set_clock(clock.clone())
+                .set_hold_duration(Duration::from_millis(10))
+                .set_lock_state_provider(FakeLockState::default());
+
+            let model = HumanModel::new()
+                .set_words_per_minute(100)
+                .set_typo_rate(typo_rate)
+                .set_correction_delay(Duration::from_millis(500));
+            let options = TypingOptions::new().set_human_model(model).set_seed(7);
+
+            keyboard
+                .type_text(&KeyboardLayout::us(), "hello world", &options)
+                .unwrap();
+
+            (sent(&transport), clock.sleeps())
+        };
+
+        let (reports, sleeps) = type_text(0.0);
+
+        assert_eq!((reports.clone(), sleeps.clone()), type_text(0.0));
+        assert_eq!(reports.len(), 22);
+        assert!(sleeps
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|d| { *d >= Duration::from_millis(50) && *d <= Duration::from_millis(200) }));
+
+        let (reports, sleeps) = type_text(1.0);
+        let backspace = (0x00, vec![0x2A]);
+
+        assert_eq!(reports.iter().filter(|r| **r == backspace).count(), 10);
+        assert_eq!(
+            sleeps
+                .iter()
+                .filter(|d| **d == Duration::from_millis(500))
+                .count(),
+            10
+        );
+
+        // Removing the typos and the backspaces leaves the keys of "hello world".
+        let kept: Vec<u8> = reports
+            .iter()
+            .enumerate()
+            .filter(|(i, r)| r.1.len() == 1 && reports.get(i + 2) != Some(&backspace))
+            .filter(|(_, r)| **r != backspace)
+            .map(|(_, r)| r.1[0])
+            .collect();
+
+        assert_eq!(
+            kept,
+            vec![0x0B, 0x08, 0x0F, 0x0F, 0x12, 0x2C, 0x1A, 0x12, 0x15, 0x0F, 0x07]
+        );
+    }
+
+    #[test]
+    fn test_type_text_overlapped() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_duration(Duration::from_millis(10))
+            .set_lock_state_provider(FakeLockState::default());
+
+        let options = TypingOptions::new()
+            .set_delay(Duration::from_millis(1))
+            .set_overlapped(true);
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "hello", &options)
+            .unwrap();
+
+        assert_eq!(
+            sent(&transport),
+            vec![
+                (0x00, vec![0x0B]),
+                (0x00, vec![0x08, 0x0B]),
+                (0x00, vec![0x08, 0x0B, 0x0F]),
+                (0x00, vec![0x08, 0x0B]),
+                (0x00, vec![0x08, 0x0B, 0x0F]),
+                (0x00, vec![0x08, 0x0B, 0x0F, 0x12]),
+                (0x00, vec![]),
+            ]
+        );
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(1); 7]);
+    }
+
+    #[test]
+    fn test_type_text_overlapped_releases_after_hold_duration() {
+        let transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+        let keyboard = Keyboard::from_transport(transport.clone())
+            .set_clock(clock.clone())
+            .set_hold_duration(Duration::from_millis(25))
+            .set_lock_state_provider(FakeLockState::default());
+
+        let options = TypingOptions::new()
+            .set_delay(Duration::from_millis(10))
+            .set_overlapped(true);
+
+        keyboard
+            .type_text(&KeyboardLayout::us(), "abcdefgh", &options)
+            .unwrap();
+
+        let reports = sent(&transport);
+        let sleeps = clock.sleeps();
+        let mut now = Duration::from_millis(0);
+        let mut pressed_at = HashMap::new();
+        let mut longest = Duration::from_millis(0);
+
+        assert_eq!(reports.len(), sleeps.len());
+
+        for ((_, keys), pause) in reports.iter().zip(sleeps) {
+            pressed_at.retain(|key, at| {
+                let down = keys.contains(key);
+
+                if !down {
+                    longest = longest.max(now - *at);
+                }
+
+                down
+            });
+
+            for key in keys {
+                pressed_at.entry(*key).or_insert(now);
+            }
+
+            now += pause;
+        }
+
+        assert!(pressed_at.is_empty());
+        assert_eq!(longest, Duration::from_millis(25));
+        assert!(reports.iter().any(|(_, keys)| keys.len() > 1));
     }
 }
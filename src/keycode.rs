@@ -0,0 +1,716 @@
+use crate::keyboard::{KeyboardKey, KeyboardModifierKey};
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+
+/// Windows virtual-key code, as found in `KBDLLHOOKSTRUCT::vkCode` or `WM_KEYDOWN`.
+///
+/// Virtual keys of punctuation depend on the layout, the ones used here are those of the
+/// US layout.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct VirtualKey(pub u8);
+
+/// Scan code of set 1, extended codes having their 0xE0 or 0xE1 prefix in the high byte,
+/// as returned by `MapVirtualKey` with `MAPVK_VK_TO_VSC_EX`.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct ScanCode(pub u16);
+
+/// Linux input event code, one of the `KEY_*` constants of linux/input-event-codes.h.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct EvdevCode(pub u16);
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum KeyCodeError {
+    UnknownVirtualKey(u8),
+    UnknownScanCode(u16),
+    UnknownEvdevCode(u16),
+    NoVirtualKey(KeyboardKey),
+    NoScanCode(KeyboardKey),
+    NoEvdevCode(KeyboardKey),
+}
+
+impl Display for KeyCodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            KeyCodeError::UnknownVirtualKey(code) => write!(f, "unknown virtual key {:#04X}", code),
+            KeyCodeError::UnknownScanCode(code) => write!(f, "unknown scan code {:#06X}", code),
+            KeyCodeError::UnknownEvdevCode(code) => write!(f, "unknown evdev code {}", code),
+            KeyCodeError::NoVirtualKey(key) => write!(f, "key {} has no virtual key", key),
+            KeyCodeError::NoScanCode(key) => write!(f, "key {} has no scan code", key),
+            KeyCodeError::NoEvdevCode(key) => write!(f, "key {} has no evdev code", key),
+        }
+    }
+}
+
+// Keys sharing a code come first in the tables, so that they are the ones the code maps back to.
+
+// Virtual-key codes may be found at "Virtual-Key Codes".
+// https://docs.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+const VIRTUAL_KEYS: [(KeyboardKey, u8); 108] = [
+    (KeyboardKey::A, 0x41),
+    (KeyboardKey::B, 0x42),
+    (KeyboardKey::C, 0x43),
+    (KeyboardKey::D, 0x44),
+    (KeyboardKey::E, 0x45),
+    (KeyboardKey::F, 0x46),
+    (KeyboardKey::G, 0x47),
+    (KeyboardKey::H, 0x48),
+    (KeyboardKey::I, 0x49),
+    (KeyboardKey::J, 0x4A),
+    (KeyboardKey::K, 0x4B),
+    (KeyboardKey::L, 0x4C),
+    (KeyboardKey::M, 0x4D),
+    (KeyboardKey::N, 0x4E),
+    (KeyboardKey::O, 0x4F),
+    (KeyboardKey::P, 0x50),
+    (KeyboardKey::Q, 0x51),
+    (KeyboardKey::R, 0x52),
+    (KeyboardKey::S, 0x53),
+    (KeyboardKey::T, 0x54),
+    (KeyboardKey::U, 0x55),
+    (KeyboardKey::V, 0x56),
+    (KeyboardKey::W, 0x57),
+    (KeyboardKey::X, 0x58),
+    (KeyboardKey::Y, 0x59),
+    (KeyboardKey::Z, 0x5A),
+    (KeyboardKey::Num1, 0x31),
+    (KeyboardKey::Num2, 0x32),
+    (KeyboardKey::Num3, 0x33),
+    (KeyboardKey::Num4, 0x34),
+    (KeyboardKey::Num5, 0x35),
+    (KeyboardKey::Num6, 0x36),
+    (KeyboardKey::Num7, 0x37),
+    (KeyboardKey::Num8, 0x38),
+    (KeyboardKey::Num9, 0x39),
+    (KeyboardKey::Num0, 0x30),
+    (KeyboardKey::Enter, 0x0D),
+    (KeyboardKey::Esc, 0x1B),
+    (KeyboardKey::Del, 0x08),
+    (KeyboardKey::Tab, 0x09),
+    (KeyboardKey::Space, 0x20),
+    (KeyboardKey::Minus, 0xBD),
+    (KeyboardKey::Equal, 0xBB),
+    (KeyboardKey::LeftBracket, 0xDB),
+    (KeyboardKey::RightBracket, 0xDD),
+    (KeyboardKey::Backslash, 0xDC),
+    (KeyboardKey::Semicolon, 0xBA),
+    (KeyboardKey::Quote, 0xDE),
+    (KeyboardKey::Grave, 0xC0),
+    (KeyboardKey::Comma, 0xBC),
+    (KeyboardKey::Period, 0xBE),
+    (KeyboardKey::Slash, 0xBF),
+    (KeyboardKey::CapsLock, 0x14),
+    (KeyboardKey::F1, 0x70),
+    (KeyboardKey::F2, 0x71),
+    (KeyboardKey::F3, 0x72),
+    (KeyboardKey::F4, 0x73),
+    (KeyboardKey::F5, 0x74),
+    (KeyboardKey::F6, 0x75),
+    (KeyboardKey::F7, 0x76),
+    (KeyboardKey::F8, 0x77),
+    (KeyboardKey::F9, 0x78),
+    (KeyboardKey::F10, 0x79),
+    (KeyboardKey::F11, 0x7A),
+    (KeyboardKey::F12, 0x7B),
+    (KeyboardKey::PrintScreen, 0x2C),
+    (KeyboardKey::ScrollLock, 0x91),
+    (KeyboardKey::Pause, 0x13),
+    (KeyboardKey::Insert, 0x2D),
+    (KeyboardKey::Home, 0x24),
+    (KeyboardKey::PageUp, 0x21),
+    (KeyboardKey::DeleteForward, 0x2E),
+    (KeyboardKey::End, 0x23),
+    (KeyboardKey::PageDown, 0x22),
+    (KeyboardKey::RightArrow, 0x27),
+    (KeyboardKey::LeftArrow, 0x25),
+    (KeyboardKey::DownArrow, 0x28),
+    (KeyboardKey::UpArrow, 0x26),
+    (KeyboardKey::NumLock, 0x90),
+    (KeyboardKey::KeypadSlash, 0x6F),
+    (KeyboardKey::KeypadAsterisk, 0x6A),
+    (KeyboardKey::KeypadMinus, 0x6D),
+    (KeyboardKey::KeypadPlus, 0x6B),
+    // Told apart from the enter key by the extended flag only.
+    (KeyboardKey::KeypadEnter, 0x0D),
+    (KeyboardKey::Keypad1, 0x61),
+    (KeyboardKey::Keypad2, 0x62),
+    (KeyboardKey::Keypad3, 0x63),
+    (KeyboardKey::Keypad4, 0x64),
+    (KeyboardKey::Keypad5, 0x65),
+    (KeyboardKey::Keypad6, 0x66),
+    (KeyboardKey::Keypad7, 0x67),
+    (KeyboardKey::Keypad8, 0x68),
+    (KeyboardKey::Keypad9, 0x69),
+    (KeyboardKey::Keypad0, 0x60),
+    (KeyboardKey::KeypadPeriod, 0x6E),
+    (KeyboardKey::NonUsBackslash, 0xE2),
+    (KeyboardKey::Application, 0x5D),
+    (KeyboardKey::Mute, 0xAD),
+    (KeyboardKey::VolumeUp, 0xAF),
+    (KeyboardKey::VolumeDown, 0xAE),
+    (KeyboardKey::International1, 0xC1),
+    (KeyboardKey::International4, 0x1C),
+    (KeyboardKey::International5, 0x1D),
+    (KeyboardKey::Lang1, 0x15),
+    (KeyboardKey::Lang2, 0x19),
+    (KeyboardKey::Lang3, 0xF1),
+    (KeyboardKey::Lang4, 0xF2),
+    (KeyboardKey::Lang5, 0xF3),
+];
+
+const MODIFIER_VIRTUAL_KEYS: [(KeyboardModifierKey, u8); 11] = [
+    (KeyboardModifierKey::LeftControl, 0xA2),
+    (KeyboardModifierKey::LeftShift, 0xA0),
+    (KeyboardModifierKey::LeftAlt, 0xA4),
+    (KeyboardModifierKey::LeftWindows, 0x5B),
+    (KeyboardModifierKey::RightControl, 0xA3),
+    (KeyboardModifierKey::RightShift, 0xA1),
+    (KeyboardModifierKey::RightAlt, 0xA5),
+    (KeyboardModifierKey::RightWindows, 0x5C),
+    // Window messages report VK_CONTROL, VK_SHIFT and VK_MENU whatever the side.
+    (KeyboardModifierKey::LeftControl, 0x11),
+    (KeyboardModifierKey::LeftShift, 0x10),
+    (KeyboardModifierKey::LeftAlt, 0x12),
+];
+
+// Scan codes may be found at "Keyboard Scan Code Specification", scan code set 1.
+const SCAN_CODES: [(KeyboardKey, u16); 112] = [
+    (KeyboardKey::A, 0x1E),
+    (KeyboardKey::B, 0x30),
+    (KeyboardKey::C, 0x2E),
+    (KeyboardKey::D, 0x20),
+    (KeyboardKey::E, 0x12),
+    (KeyboardKey::F, 0x21),
+    (KeyboardKey::G, 0x22),
+    (KeyboardKey::H, 0x23),
+    (KeyboardKey::I, 0x17),
+    (KeyboardKey::J, 0x24),
+    (KeyboardKey::K, 0x25),
+    (KeyboardKey::L, 0x26),
+    (KeyboardKey::M, 0x32),
+    (KeyboardKey::N, 0x31),
+    (KeyboardKey::O, 0x18),
+    (KeyboardKey::P, 0x19),
+    (KeyboardKey::Q, 0x10),
+    (KeyboardKey::R, 0x13),
+    (KeyboardKey::S, 0x1F),
+    (KeyboardKey::T, 0x14),
+    (KeyboardKey::U, 0x16),
+    (KeyboardKey::V, 0x2F),
+    (KeyboardKey::W, 0x11),
+    (KeyboardKey::X, 0x2D),
+    (KeyboardKey::Y, 0x15),
+    (KeyboardKey::Z, 0x2C),
+    (KeyboardKey::Num1, 0x02),
+    (KeyboardKey::Num2, 0x03),
+    (KeyboardKey::Num3, 0x04),
+    (KeyboardKey::Num4, 0x05),
+    (KeyboardKey::Num5, 0x06),
+    (KeyboardKey::Num6, 0x07),
+    (KeyboardKey::Num7, 0x08),
+    (KeyboardKey::Num8, 0x09),
+    (KeyboardKey::Num9, 0x0A),
+    (KeyboardKey::Num0, 0x0B),
+    (KeyboardKey::Enter, 0x1C),
+    (KeyboardKey::Esc, 0x01),
+    (KeyboardKey::Del, 0x0E),
+    (KeyboardKey::Tab, 0x0F),
+    (KeyboardKey::Space, 0x39),
+    (KeyboardKey::Minus, 0x0C),
+    (KeyboardKey::Equal, 0x0D),
+    (KeyboardKey::LeftBracket, 0x1A),
+    (KeyboardKey::RightBracket, 0x1B),
+    (KeyboardKey::Backslash, 0x2B),
+    (KeyboardKey::Semicolon, 0x27),
+    (KeyboardKey::Quote, 0x28),
+    (KeyboardKey::Grave, 0x29),
+    (KeyboardKey::Comma, 0x33),
+    (KeyboardKey::Period, 0x34),
+    (KeyboardKey::Slash, 0x35),
+    (KeyboardKey::CapsLock, 0x3A),
+    (KeyboardKey::F1, 0x3B),
+    (KeyboardKey::F2, 0x3C),
+    (KeyboardKey::F3, 0x3D),
+    (KeyboardKey::F4, 0x3E),
+    (KeyboardKey::F5, 0x3F),
+    (KeyboardKey::F6, 0x40),
+    (KeyboardKey::F7, 0x41),
+    (KeyboardKey::F8, 0x42),
+    (KeyboardKey::F9, 0x43),
+    (KeyboardKey::F10, 0x44),
+    (KeyboardKey::F11, 0x57),
+    (KeyboardKey::F12, 0x58),
+    (KeyboardKey::PrintScreen, 0xE037),
+    (KeyboardKey::ScrollLock, 0x46),
+    (KeyboardKey::Pause, 0xE11D),
+    (KeyboardKey::Insert, 0xE052),
+    (KeyboardKey::Home, 0xE047),
+    (KeyboardKey::PageUp, 0xE049),
+    (KeyboardKey::DeleteForward, 0xE053),
+    (KeyboardKey::End, 0xE04F),
+    (KeyboardKey::PageDown, 0xE051),
+    (KeyboardKey::RightArrow, 0xE04D),
+    (KeyboardKey::LeftArrow, 0xE04B),
+    (KeyboardKey::DownArrow, 0xE050),
+    (KeyboardKey::UpArrow, 0xE048),
+    (KeyboardKey::NumLock, 0x45),
+    (KeyboardKey::KeypadSlash, 0xE035),
+    (KeyboardKey::KeypadAsterisk, 0x37),
+    (KeyboardKey::KeypadMinus, 0x4A),
+    (KeyboardKey::KeypadPlus, 0x4E),
+    (KeyboardKey::KeypadEnter, 0xE01C),
+    (KeyboardKey::Keypad1, 0x4F),
+    (KeyboardKey::Keypad2, 0x50),
+    (KeyboardKey::Keypad3, 0x51),
+    (KeyboardKey::Keypad4, 0x4B),
+    (KeyboardKey::Keypad5, 0x4C),
+    (KeyboardKey::Keypad6, 0x4D),
+    (KeyboardKey::Keypad7, 0x47),
+    (KeyboardKey::Keypad8, 0x48),
+    (KeyboardKey::Keypad9, 0x49),
+    (KeyboardKey::Keypad0, 0x52),
+    (KeyboardKey::KeypadPeriod, 0x53),
+    (KeyboardKey::NonUsBackslash, 0x56),
+    (KeyboardKey::Application, 0xE05D),
+    (KeyboardKey::Mute, 0xE020),
+    (KeyboardKey::VolumeUp, 0xE030),
+    (KeyboardKey::VolumeDown, 0xE02E),
+    (KeyboardKey::International1, 0x73),
+    (KeyboardKey::International2, 0x70),
+    (KeyboardKey::International3, 0x7D),
+    (KeyboardKey::International4, 0x79),
+    (KeyboardKey::International5, 0x7B),
+    (KeyboardKey::International6, 0x5C),
+    (KeyboardKey::Lang1, 0xF2),
+    (KeyboardKey::Lang2, 0xF1),
+    (KeyboardKey::Lang3, 0x78),
+    (KeyboardKey::Lang4, 0x77),
+    (KeyboardKey::Lang5, 0x76),
+    // Where the backslash key of a US keyboard sits, on ISO keyboards.
+    (KeyboardKey::NonUsHash, 0x2B),
+];
+
+const MODIFIER_SCAN_CODES: [(KeyboardModifierKey, u16); 8] = [
+    (KeyboardModifierKey::LeftControl, 0x1D),
+    (KeyboardModifierKey::LeftShift, 0x2A),
+    (KeyboardModifierKey::LeftAlt, 0x38),
+    (KeyboardModifierKey::LeftWindows, 0xE05B),
+    (KeyboardModifierKey::RightControl, 0xE01D),
+    (KeyboardModifierKey::RightShift, 0x36),
+    (KeyboardModifierKey::RightAlt, 0xE038),
+    (KeyboardModifierKey::RightWindows, 0xE05C),
+];
+
+// Key codes may be found at linux/input-event-codes.h, mapped as drivers/hid/hid-input.c does.
+const EVDEV_CODES: [(KeyboardKey, u16); 112] = [
+    (KeyboardKey::A, 30),
+    (KeyboardKey::B, 48),
+    (KeyboardKey::C, 46),
+    (KeyboardKey::D, 32),
+    (KeyboardKey::E, 18),
+    (KeyboardKey::F, 33),
+    (KeyboardKey::G, 34),
+    (KeyboardKey::H, 35),
+    (KeyboardKey::I, 23),
+    (KeyboardKey::J, 36),
+    (KeyboardKey::K, 37),
+    (KeyboardKey::L, 38),
+    (KeyboardKey::M, 50),
+    (KeyboardKey::N, 49),
+    (KeyboardKey::O, 24),
+    (KeyboardKey::P, 25),
+    (KeyboardKey::Q, 16),
+    (KeyboardKey::R, 19),
+    (KeyboardKey::S, 31),
+    (KeyboardKey::T, 20),
+    (KeyboardKey::U, 22),
+    (KeyboardKey::V, 47),
+    (KeyboardKey::W, 17),
+    (KeyboardKey::X, 45),
+    (KeyboardKey::Y, 21),
+    (KeyboardKey::Z, 44),
+    (KeyboardKey::Num1, 2),
+    (KeyboardKey::Num2, 3),
+    (KeyboardKey::Num3, 4),
+    (KeyboardKey::Num4, 5),
+    (KeyboardKey::Num5, 6),
+    (KeyboardKey::Num6, 7),
+    (KeyboardKey::Num7, 8),
+    (KeyboardKey::Num8, 9),
+    (KeyboardKey::Num9, 10),
+    (KeyboardKey::Num0, 11),
+    (KeyboardKey::Enter, 28),
+    (KeyboardKey::Esc, 1),
+    (KeyboardKey::Del, 14),
+    (KeyboardKey::Tab, 15),
+    (KeyboardKey::Space, 57),
+    (KeyboardKey::Minus, 12),
+    (KeyboardKey::Equal, 13),
+    (KeyboardKey::LeftBracket, 26),
+    (KeyboardKey::RightBracket, 27),
+    (KeyboardKey::Backslash, 43),
+    (KeyboardKey::Semicolon, 39),
+    (KeyboardKey::Quote, 40),
+    (KeyboardKey::Grave, 41),
+    (KeyboardKey::Comma, 51),
+    (KeyboardKey::Period, 52),
+    (KeyboardKey::Slash, 53),
+    (KeyboardKey::CapsLock, 58),
+    (KeyboardKey::F1, 59),
+    (KeyboardKey::F2, 60),
+    (KeyboardKey::F3, 61),
+    (KeyboardKey::F4, 62),
+    (KeyboardKey::F5, 63),
+    (KeyboardKey::F6, 64),
+    (KeyboardKey::F7, 65),
+    (KeyboardKey::F8, 66),
+    (KeyboardKey::F9, 67),
+    (KeyboardKey::F10, 68),
+    (KeyboardKey::F11, 87),
+    (KeyboardKey::F12, 88),
+    (KeyboardKey::PrintScreen, 99),
+    (KeyboardKey::ScrollLock, 70),
+    (KeyboardKey::Pause, 119),
+    (KeyboardKey::Insert, 110),
+    (KeyboardKey::Home, 102),
+    (KeyboardKey::PageUp, 104),
+    (KeyboardKey::DeleteForward, 111),
+    (KeyboardKey::End, 107),
+    (KeyboardKey::PageDown, 109),
+    (KeyboardKey::RightArrow, 106),
+    (KeyboardKey::LeftArrow, 105),
+    (KeyboardKey::DownArrow, 108),
+    (KeyboardKey::UpArrow, 103),
+    (KeyboardKey::NumLock, 69),
+    (KeyboardKey::KeypadSlash, 98),
+    (KeyboardKey::KeypadAsterisk, 55),
+    (KeyboardKey::KeypadMinus, 74),
+    (KeyboardKey::KeypadPlus, 78),
+    (KeyboardKey::KeypadEnter, 96),
+    (KeyboardKey::Keypad1, 79),
+    (KeyboardKey::Keypad2, 80),
+    (KeyboardKey::Keypad3, 81),
+    (KeyboardKey::Keypad4, 75),
+    (KeyboardKey::Keypad5, 76),
+    (KeyboardKey::Keypad6, 77),
+    (KeyboardKey::Keypad7, 71),
+    (KeyboardKey::Keypad8, 72),
+    (KeyboardKey::Keypad9, 73),
+    (KeyboardKey::Keypad0, 82),
+    (KeyboardKey::KeypadPeriod, 83),
+    (KeyboardKey::NonUsBackslash, 86),
+    (KeyboardKey::Application, 127),
+    (KeyboardKey::Mute, 113),
+    (KeyboardKey::VolumeUp, 115),
+    (KeyboardKey::VolumeDown, 114),
+    (KeyboardKey::International1, 89),
+    (KeyboardKey::International2, 93),
+    (KeyboardKey::International3, 124),
+    (KeyboardKey::International4, 92),
+    (KeyboardKey::International5, 94),
+    (KeyboardKey::International6, 95),
+    (KeyboardKey::Lang1, 122),
+    (KeyboardKey::Lang2, 123),
+    (KeyboardKey::Lang3, 90),
+    (KeyboardKey::Lang4, 91),
+    (KeyboardKey::Lang5, 85),
+    // Linux has a single code for the backslash key of US and ISO keyboards.
+    (KeyboardKey::NonUsHash, 43),
+];
+
+const MODIFIER_EVDEV_CODES: [(KeyboardModifierKey, u16); 8] = [
+    (KeyboardModifierKey::LeftControl, 29),
+    (KeyboardModifierKey::LeftShift, 42),
+    (KeyboardModifierKey::LeftAlt, 56),
+    (KeyboardModifierKey::LeftWindows, 125),
+    (KeyboardModifierKey::RightControl, 97),
+    (KeyboardModifierKey::RightShift, 54),
+    (KeyboardModifierKey::RightAlt, 100),
+    (KeyboardModifierKey::RightWindows, 126),
+];
+
+fn find_code<K: PartialEq, C: Copy>(table: &[(K, C)], key: &K) -> Option<C> {
+    table.iter().find(|(k, _)| k == key).map(|(_, code)| *code)
+}
+
+fn find_key<K: Copy, C: PartialEq>(table: &[(K, C)], code: &C) -> Option<K> {
+    table.iter().find(|(_, c)| c == code).map(|(key, _)| *key)
+}
+
+impl TryFrom<KeyboardKey> for VirtualKey {
+    type Error = KeyCodeError;
+
+    fn try_from(key: KeyboardKey) -> Result<Self, Self::Error> {
+        find_code(&VIRTUAL_KEYS, &key)
+            .map(VirtualKey)
+            .ok_or(KeyCodeError::NoVirtualKey(key))
+    }
+}
+
+impl TryFrom<VirtualKey> for KeyboardKey {
+    type Error = KeyCodeError;
+
+    fn try_from(virtual_key: VirtualKey) -> Result<Self, Self::Error> {
+        find_key(&VIRTUAL_KEYS, &virtual_key.0)
+            .ok_or(KeyCodeError::UnknownVirtualKey(virtual_key.0))
+    }
+}
+
+impl From<KeyboardModifierKey> for VirtualKey {
+    fn from(key: KeyboardModifierKey) -> Self {
+        VirtualKey(find_code(&MODIFIER_VIRTUAL_KEYS, &key).expect("modifier without virtual key"))
+    }
+}
+
+impl TryFrom<VirtualKey> for KeyboardModifierKey {
+    type Error = KeyCodeError;
+
+    fn try_from(virtual_key: VirtualKey) -> Result<Self, Self::Error> {
+        find_key(&MODIFIER_VIRTUAL_KEYS, &virtual_key.0)
+            .ok_or(KeyCodeError::UnknownVirtualKey(virtual_key.0))
+    }
+}
+
+impl TryFrom<KeyboardKey> for ScanCode {
+    type Error = KeyCodeError;
+
+    fn try_from(key: KeyboardKey) -> Result<Self, Self::Error> {
+        find_code(&SCAN_CODES, &key)
+            .map(ScanCode)
+            .ok_or(KeyCodeError::NoScanCode(key))
+    }
+}
+
+impl TryFrom<ScanCode> for KeyboardKey {
+    type Error = KeyCodeError;
+
+    fn try_from(scan_code: ScanCode) -> Result<Self, Self::Error> {
+        find_key(&SCAN_CODES, &scan_code.0).ok_or(KeyCodeError::UnknownScanCode(scan_code.0))
+    }
+}
+
+impl From<KeyboardModifierKey> for ScanCode {
+    fn from(key: KeyboardModifierKey) -> Self {
+        ScanCode(find_code(&MODIFIER_SCAN_CODES, &key).expect("modifier without scan code"))
+    }
+}
+
+impl TryFrom<ScanCode> for KeyboardModifierKey {
+    type Error = KeyCodeError;
+
+    fn try_from(scan_code: ScanCode) -> Result<Self, Self::Error> {
+        find_key(&MODIFIER_SCAN_CODES, &scan_code.0)
+            .ok_or(KeyCodeError::UnknownScanCode(scan_code.0))
+    }
+}
+
+impl TryFrom<KeyboardKey> for EvdevCode {
+    type Error = KeyCodeError;
+
+    fn try_from(key: KeyboardKey) -> Result<Self, Self::Error> {
+        find_code(&EVDEV_CODES, &key)
+            .map(EvdevCode)
+            .ok_or(KeyCodeError::NoEvdevCode(key))
+    }
+}
+
+impl TryFrom<EvdevCode> for KeyboardKey {
+    type Error = KeyCodeError;
+
+    fn try_from(evdev_code: EvdevCode) -> Result<Self, Self::Error> {
+        find_key(&EVDEV_CODES, &evdev_code.0).ok_or(KeyCodeError::UnknownEvdevCode(evdev_code.0))
+    }
+}
+
+impl From<KeyboardModifierKey> for EvdevCode {
+    fn from(key: KeyboardModifierKey) -> Self {
+        EvdevCode(find_code(&MODIFIER_EVDEV_CODES, &key).expect("modifier without evdev code"))
+    }
+}
+
+impl TryFrom<EvdevCode> for KeyboardModifierKey {
+    type Error = KeyCodeError;
+
+    fn try_from(evdev_code: EvdevCode) -> Result<Self, Self::Error> {
+        find_key(&MODIFIER_EVDEV_CODES, &evdev_code.0)
+            .ok_or(KeyCodeError::UnknownEvdevCode(evdev_code.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KEY_NAMES, MODIFIER_NAMES};
+    use crate::keycode::{
+        EvdevCode, KeyCodeError, ScanCode, VirtualKey, EVDEV_CODES, MODIFIER_EVDEV_CODES,
+        MODIFIER_SCAN_CODES, MODIFIER_VIRTUAL_KEYS, SCAN_CODES, VIRTUAL_KEYS,
+    };
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_virtual_keys() {
+        assert_eq!(VirtualKey::try_from(KeyboardKey::A), Ok(VirtualKey(0x41)));
+        assert_eq!(
+            VirtualKey::try_from(KeyboardKey::Quote),
+            Ok(VirtualKey(0xDE))
+        );
+        assert_eq!(
+            KeyboardKey::try_from(VirtualKey(0x0D)),
+            Ok(KeyboardKey::Enter)
+        );
+        assert_eq!(
+            KeyboardModifierKey::try_from(VirtualKey(0x11)),
+            Ok(KeyboardModifierKey::LeftControl)
+        );
+        assert_eq!(
+            VirtualKey::from(KeyboardModifierKey::RightAlt),
+            VirtualKey(0xA5)
+        );
+
+        assert_eq!(
+            KeyboardKey::try_from(VirtualKey(0xFF)),
+            Err(KeyCodeError::UnknownVirtualKey(0xFF))
+        );
+        assert_eq!(
+            VirtualKey::try_from(KeyboardKey::Lang9),
+            Err(KeyCodeError::NoVirtualKey(KeyboardKey::Lang9))
+        );
+    }
+
+    #[test]
+    fn test_scan_codes() {
+        assert_eq!(ScanCode::try_from(KeyboardKey::A), Ok(ScanCode(0x1E)));
+        assert_eq!(
+            KeyboardKey::try_from(ScanCode(0xE035)),
+            Ok(KeyboardKey::KeypadSlash)
+        );
+        assert_eq!(
+            KeyboardKey::try_from(ScanCode(0x35)),
+            Ok(KeyboardKey::Slash)
+        );
+        assert_eq!(
+            KeyboardKey::try_from(ScanCode(0xE11D)),
+            Ok(KeyboardKey::Pause)
+        );
+        assert_eq!(
+            KeyboardKey::try_from(ScanCode(0x2B)),
+            Ok(KeyboardKey::Backslash)
+        );
+        assert_eq!(
+            ScanCode::from(KeyboardModifierKey::RightControl),
+            ScanCode(0xE01D)
+        );
+
+        assert_eq!(
+            KeyboardKey::try_from(ScanCode(0xE01D)),
+            Err(KeyCodeError::UnknownScanCode(0xE01D))
+        );
+        assert_eq!(
+            ScanCode::try_from(KeyboardKey::International9),
+            Err(KeyCodeError::NoScanCode(KeyboardKey::International9))
+        );
+    }
+
+    #[test]
+    fn test_evdev_codes() {
+        assert_eq!(EvdevCode::try_from(KeyboardKey::A), Ok(EvdevCode(30)));
+        assert_eq!(
+            EvdevCode::try_from(KeyboardKey::Application),
+            Ok(EvdevCode(127))
+        );
+        assert_eq!(
+            KeyboardKey::try_from(EvdevCode(96)),
+            Ok(KeyboardKey::KeypadEnter)
+        );
+        assert_eq!(
+            KeyboardKey::try_from(EvdevCode(122)),
+            Ok(KeyboardKey::Lang1)
+        );
+        assert_eq!(
+            EvdevCode::from(KeyboardModifierKey::RightWindows),
+            EvdevCode(126)
+        );
+        assert_eq!(
+            KeyboardModifierKey::try_from(EvdevCode(100)),
+            Ok(KeyboardModifierKey::RightAlt)
+        );
+
+        assert_eq!(
+            KeyboardKey::try_from(EvdevCode(116)),
+            Err(KeyCodeError::UnknownEvdevCode(116))
+        );
+        assert_eq!(
+            EvdevCode::try_from(KeyboardKey::Lang6),
+            Err(KeyCodeError::NoEvdevCode(KeyboardKey::Lang6))
+        );
+    }
+
+    #[test]
+    fn test_codes_round_trip() {
+        for (_, code) in VIRTUAL_KEYS.iter() {
+            let key = KeyboardKey::try_from(VirtualKey(*code)).unwrap();
+            assert_eq!(VirtualKey::try_from(key), Ok(VirtualKey(*code)));
+        }
+
+        for (_, code) in SCAN_CODES.iter() {
+            let key = KeyboardKey::try_from(ScanCode(*code)).unwrap();
+            assert_eq!(ScanCode::try_from(key), Ok(ScanCode(*code)));
+        }
+
+        for (_, code) in EVDEV_CODES.iter() {
+            let key = KeyboardKey::try_from(EvdevCode(*code)).unwrap();
+            assert_eq!(EvdevCode::try_from(key), Ok(EvdevCode(*code)));
+        }
+
+        for (_, code) in MODIFIER_VIRTUAL_KEYS.iter() {
+            let key = KeyboardModifierKey::try_from(VirtualKey(*code)).unwrap();
+            assert_eq!(
+                KeyboardModifierKey::try_from(VirtualKey::from(key)),
+                Ok(key)
+            );
+        }
+
+        for (_, code) in MODIFIER_SCAN_CODES.iter() {
+            let key = KeyboardModifierKey::try_from(ScanCode(*code)).unwrap();
+            assert_eq!(ScanCode::from(key), ScanCode(*code));
+        }
+
+        for (_, code) in MODIFIER_EVDEV_CODES.iter() {
+            let key = KeyboardModifierKey::try_from(EvdevCode(*code)).unwrap();
+            assert_eq!(EvdevCode::from(key), EvdevCode(*code));
+        }
+    }
+
+    #[test]
+    fn test_keys_round_trip() {
+        // Keys sharing their code with another key, which the code maps back to.
+        let shared = [KeyboardKey::KeypadEnter, KeyboardKey::NonUsHash];
+
+        for (_, key) in KEY_NAMES.iter().filter(|(_, key)| !shared.contains(key)) {
+            if let Ok(virtual_key) = VirtualKey::try_from(*key) {
+                assert_eq!(KeyboardKey::try_from(virtual_key), Ok(*key));
+            }
+
+            if let Ok(scan_code) = ScanCode::try_from(*key) {
+                assert_eq!(KeyboardKey::try_from(scan_code), Ok(*key));
+            } else {
+                assert!(*key >= KeyboardKey::International7, "{}", key);
+            }
+
+            if let Ok(evdev_code) = EvdevCode::try_from(*key) {
+                assert_eq!(KeyboardKey::try_from(evdev_code), Ok(*key));
+            } else {
+                assert!(*key >= KeyboardKey::International7, "{}", key);
+            }
+        }
+
+        for (_, key) in MODIFIER_NAMES.iter() {
+            assert_eq!(
+                KeyboardModifierKey::try_from(VirtualKey::from(*key)),
+                Ok(*key)
+            );
+            assert_eq!(
+                KeyboardModifierKey::try_from(ScanCode::from(*key)),
+                Ok(*key)
+            );
+        }
+    }
+}
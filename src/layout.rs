@@ -0,0 +1,775 @@
+use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
+use crate::keycode::ScanCode;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+
+const KLC_KEYWORDS: [&str; 17] = [
+    "KBD",
+    "COPYRIGHT",
+    "COMPANY",
+    "LOCALENAME",
+    "LOCALEID",
+    "VERSION",
+    "ATTRIBUTES",
+    "SHIFTSTATE",
+    "LAYOUT",
+    "DEADKEY",
+    "LIGATURE",
+    "KEYNAME",
+    "KEYNAME_EXT",
+    "KEYNAME_DEAD",
+    "DESCRIPTIONS",
+    "LANGUAGENAMES",
+    "ENDKBD",
+];
+
+// Shift state bits as used by the SHIFTSTATE section of .klc files.
+const KLC_SHIFT: u8 = 1;
+const KLC_CONTROL: u8 = 2;
+const KLC_ALT: u8 = 4;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum KlcError {
+    InvalidEncoding,
+    MissingShiftStates,
+    MissingLayout,
+    InvalidShiftState(usize),
+    InvalidLayoutRow(usize),
+    InvalidCharacter(usize),
+    InvalidDeadKey(usize),
+    InvalidLigature(usize),
+}
+
+impl Display for KlcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            KlcError::InvalidEncoding => write!(f, "file is neither UTF-8 nor UTF-16"),
+            KlcError::MissingShiftStates => write!(f, "SHIFTSTATE section is missing"),
+            KlcError::MissingLayout => write!(f, "LAYOUT section is missing"),
+            KlcError::InvalidShiftState(line) => write!(f, "invalid shift state at line {}", line),
+            KlcError::InvalidLayoutRow(line) => write!(f, "invalid layout row at line {}", line),
+            KlcError::InvalidCharacter(line) => write!(f, "invalid character at line {}", line),
+            KlcError::InvalidDeadKey(line) => write!(f, "invalid dead key at line {}", line),
+            KlcError::InvalidLigature(line) => write!(f, "invalid ligature at line {}", line),
+        }
+    }
+}
+
+/// Maps characters to the keystrokes producing them on a given Windows keyboard layout.
+///
+/// A character reachable only through a dead key maps to two keystrokes: the dead key
+/// itself and the base character.
+#[derive(Debug, Clone)]
+pub struct KeyboardLayout {
+    name: String,
+    description: String,
+    characters: HashMap<char, Vec<KeysClick>>,
+    ligatures: HashMap<String, KeysClick>,
+    caps_lock_keys: HashSet<KeyboardKey>,
+}
+
+impl KeyboardLayout {
+    /// Built-in US QWERTY layout.
+    pub fn us() -> Self {
+        let mut characters = HashMap::new();
+
+        for &(character, key, shift) in US_CHARACTERS.iter() {
+            let keys_click = if shift {
+                KeysClick::new(key).add_modifier(KeyboardModifierKey::LeftShift)
+            } else {
+                KeysClick::new(key)
+            };
+
+            characters.insert(character, vec![keys_click]);
+        }
+
+        let caps_lock_keys = US_CHARACTERS
+            .iter()
+            .filter(|(character, _, _)| character.is_ascii_lowercase())
+            .map(|(_, key, _)| *key)
+            .collect();
+
+        KeyboardLayout {
+            name: String::from("US"),
+            description: String::from("US"),
+            characters,
+            ligatures: HashMap::new(),
+            caps_lock_keys,
+        }
+    }
+
+    /// Loads a layout from the bytes of a Microsoft Keyboard Layout Creator file.
+    ///
+    /// MSKLC saves files as UTF-16 LE with a byte order mark, UTF-8 is accepted too.
+    pub fn from_klc_bytes(bytes: &[u8]) -> Result<Self, KlcError> {
+        let text = match bytes {
+            [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes)?,
+            [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes)?,
+            [0xEF, 0xBB, 0xBF, rest @ ..] => {
+                String::from_utf8(rest.to_vec()).map_err(|_| KlcError::InvalidEncoding)?
+            }
+            _ => String::from_utf8(bytes.to_vec()).map_err(|_| KlcError::InvalidEncoding)?,
+        };
+
+        KeyboardLayout::from_klc(&text)
+    }
+
+    /// Loads a layout from the text of a Microsoft Keyboard Layout Creator file.
+    pub fn from_klc(input: &str) -> Result<Self, KlcError> {
+        KlcParser::default().parse(input)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Keystrokes to type a character, to be sent one after another with a release in between.
+    pub fn keys_for(&self, character: char) -> Option<&[KeysClick]> {
+        self.characters.get(&character).map(|k| k.as_slice())
+    }
+
+    /// Whether caps lock makes the key type its shifted character, as it does for letters.
+    pub fn is_caps_lock_key(&self, key: KeyboardKey) -> bool {
+        self.caps_lock_keys.contains(&key)
+    }
+
+    /// Keystroke producing a ligature, a single key emitting several characters.
+    pub fn keys_for_ligature(&self, ligature: &str) -> Option<&KeysClick> {
+        self.ligatures.get(ligature)
+    }
+}
+
+/// Keyboard layout on the target, choosing the key which toggles its input method editor.
+///
+/// The stock vmulti descriptor stops at usage 0x65 and drops the International and LANG keys,
+/// so `Korean` only works with drivers whose descriptor reaches usage 0x90. `KoreanRightAlt`
+/// works with the stock one.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ImeLayout {
+    /// Japanese JIS layout, where the Hankaku/Zenkaku key sits in place of the grave key.
+    Japanese,
+    /// Japanese input method on a US layout, toggled with Alt+grave.
+    JapaneseOnUs,
+    /// Korean layout, switching between Hangul and Latin input with the LANG1 key.
+    Korean,
+    /// Korean 101-key layout of Windows, where Right Alt switches between Hangul and Latin.
+    KoreanRightAlt,
+}
+
+impl ImeLayout {
+    /// Keys click switching the input method on or off.
+    pub fn toggle_keys(&self) -> KeysClick {
+        match self {
+            ImeLayout::Japanese => KeysClick::new(KeyboardKey::Grave),
+            ImeLayout::JapaneseOnUs => {
+                KeysClick::new(KeyboardKey::Grave).add_modifier(KeyboardModifierKey::LeftAlt)
+            }
+            ImeLayout::Korean => KeysClick::new(KeyboardKey::Lang1),
+            ImeLayout::KoreanRightAlt => {
+                KeysClick::empty().add_modifier(KeyboardModifierKey::RightAlt)
+            }
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], decode: fn([u8; 2]) -> u16) -> Result<String, KlcError> {
+    let chunks = bytes.chunks_exact(2);
+
+    if !chunks.remainder().is_empty() {
+        return Err(KlcError::InvalidEncoding);
+    }
+
+    let units: Vec<u16> = chunks.map(|chunk| decode([chunk[0], chunk[1]])).collect();
+
+    String::from_utf16(&units).map_err(|_| KlcError::InvalidEncoding)
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum KlcSection {
+    Header,
+    ShiftState,
+    Layout,
+    DeadKey(char),
+    Ligature,
+    Ignored,
+}
+
+enum KlcCell {
+    Empty,
+    Character(char),
+    DeadKey(char),
+    Ligature,
+}
+
+#[derive(Default)]
+struct KlcParser {
+    name: String,
+    description: String,
+    shift_states: Vec<u8>,
+    has_layout: bool,
+    characters: HashMap<char, Vec<KeysClick>>,
+    dead_keys: HashMap<char, KeysClick>,
+    // Base and composed character pairs, resolved once the whole file is read.
+    compositions: Vec<(char, char, char)>,
+    virtual_keys: HashMap<String, KeyboardKey>,
+    ligatures: HashMap<String, KeysClick>,
+    caps_lock_keys: HashSet<KeyboardKey>,
+}
+
+impl KlcParser {
+    fn parse(mut self, input: &str) -> Result<KeyboardLayout, KlcError> {
+        let mut section = KlcSection::Header;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let tokens: Vec<&str> = line
+                .split_whitespace()
+                .take_while(|t| !t.starts_with("//"))
+                .collect();
+
+            let first = match tokens.first() {
+                Some(first) => *first,
+                None => continue,
+            };
+
+            if KLC_KEYWORDS.contains(&first) {
+                section = self.parse_keyword(&tokens, line, line_number)?;
+                continue;
+            }
+
+            match section {
+                KlcSection::ShiftState => {
+                    let state = first
+                        .parse::<u8>()
+                        .map_err(|_| KlcError::InvalidShiftState(line_number))?;
+
+                    self.shift_states.push(state);
+                }
+                KlcSection::Layout => self.parse_layout_row(&tokens, line_number)?,
+                KlcSection::DeadKey(dead_key) => {
+                    let (base, composed) = match tokens.as_slice() {
+                        [base, composed, ..] => (parse_hex(base), parse_hex(composed)),
+                        _ => (None, None),
+                    };
+
+                    match (base, composed) {
+                        (Some(base), Some(composed)) => {
+                            self.compositions.push((dead_key, base, composed))
+                        }
+                        _ => return Err(KlcError::InvalidDeadKey(line_number)),
+                    }
+                }
+                KlcSection::Ligature => self.parse_ligature_row(&tokens, line_number)?,
+                KlcSection::Header | KlcSection::Ignored => {}
+            }
+        }
+
+        if self.shift_states.is_empty() {
+            return Err(KlcError::MissingShiftStates);
+        }
+
+        if !self.has_layout {
+            return Err(KlcError::MissingLayout);
+        }
+
+        for (dead_key, base, composed) in self.compositions.iter() {
+            if self.characters.contains_key(composed) {
+                continue;
+            }
+
+            let dead_keys_click = self.dead_keys.get(dead_key);
+            let base_keys_click = self.characters.get(base).filter(|k| k.len() == 1);
+
+            if let (Some(dead_keys_click), Some(base_keys_click)) =
+                (dead_keys_click, base_keys_click)
+            {
+                let strokes = vec![*dead_keys_click, base_keys_click[0]];
+                self.characters.insert(*composed, strokes);
+            }
+        }
+
+        Ok(KeyboardLayout {
+            name: self.name,
+            description: self.description,
+            characters: self.characters,
+            ligatures: self.ligatures,
+            caps_lock_keys: self.caps_lock_keys,
+        })
+    }
+
+    fn parse_keyword(
+        &mut self,
+        tokens: &[&str],
+        line: &str,
+        line_number: usize,
+    ) -> Result<KlcSection, KlcError> {
+        let section = match tokens[0] {
+            "KBD" => {
+                self.name = tokens.get(1).map(|n| n.to_string()).unwrap_or_default();
+                self.description = line
+                    .split('"')
+                    .nth(1)
+                    .map(|d| d.to_string())
+                    .unwrap_or_default();
+
+                KlcSection::Header
+            }
+            "SHIFTSTATE" => KlcSection::ShiftState,
+            "LAYOUT" => {
+                self.has_layout = true;
+                KlcSection::Layout
+            }
+            "DEADKEY" => match tokens.get(1).and_then(|t| parse_hex(t)) {
+                Some(dead_key) => KlcSection::DeadKey(dead_key),
+                None => return Err(KlcError::InvalidDeadKey(line_number)),
+            },
+            "LIGATURE" => KlcSection::Ligature,
+            "COPYRIGHT" | "COMPANY" | "LOCALENAME" | "LOCALEID" | "VERSION" => KlcSection::Header,
+            _ => KlcSection::Ignored,
+        };
+
+        Ok(section)
+    }
+
+    fn parse_layout_row(&mut self, tokens: &[&str], line_number: usize) -> Result<(), KlcError> {
+        // Rows starting with -1 hold SGCap variants of the previous row.
+        if tokens[0] == "-1" {
+            return Ok(());
+        }
+
+        if tokens.len() < 3 {
+            return Err(KlcError::InvalidLayoutRow(line_number));
+        }
+
+        let scan_code = u16::from_str_radix(tokens[0], 16)
+            .map_err(|_| KlcError::InvalidLayoutRow(line_number))?;
+
+        let key = match KeyboardKey::try_from(ScanCode(scan_code)) {
+            Ok(key) => key,
+            Err(_) => return Ok(()),
+        };
+
+        self.virtual_keys.insert(tokens[1].to_string(), key);
+
+        // The Cap column is either SGCap or flags, the lowest one telling that caps lock acts
+        // like shift on the key.
+        if matches!(tokens[2].parse::<u8>(), Ok(cap) if cap & 1 != 0) {
+            self.caps_lock_keys.insert(key);
+        }
+
+        for (column, cell) in tokens[3..].iter().enumerate() {
+            let shift_state = match self.shift_states.get(column) {
+                Some(shift_state) => *shift_state,
+                None => break,
+            };
+
+            let keys_click = match keys_click_for_shift_state(key, shift_state) {
+                Some(keys_click) => keys_click,
+                None => continue,
+            };
+
+            match parse_cell(cell).ok_or(KlcError::InvalidCharacter(line_number))? {
+                KlcCell::Character(character) => {
+                    insert_keys_click(&mut self.characters, character, keys_click)
+                }
+                KlcCell::DeadKey(character) => {
+                    self.dead_keys.entry(character).or_insert(keys_click);
+                }
+                KlcCell::Empty | KlcCell::Ligature => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_ligature_row(&mut self, tokens: &[&str], line_number: usize) -> Result<(), KlcError> {
+        if tokens.len() < 3 {
+            return Err(KlcError::InvalidLigature(line_number));
+        }
+
+        let key = match self.virtual_keys.get(tokens[0]) {
+            Some(key) => *key,
+            None => return Ok(()),
+        };
+
+        let shift_state = tokens[1]
+            .parse::<usize>()
+            .ok()
+            .and_then(|column| self.shift_states.get(column))
+            .ok_or(KlcError::InvalidLigature(line_number))?;
+
+        let ligature = tokens[2..]
+            .iter()
+            .map(|t| parse_hex(t))
+            .collect::<Option<String>>()
+            .ok_or(KlcError::InvalidLigature(line_number))?;
+
+        if let Some(keys_click) = keys_click_for_shift_state(key, *shift_state) {
+            self.ligatures.insert(ligature, keys_click);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_hex(value: &str) -> Option<char> {
+    u32::from_str_radix(value, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+}
+
+fn parse_cell(cell: &str) -> Option<KlcCell> {
+    if cell == "-1" {
+        return Some(KlcCell::Empty);
+    }
+
+    if cell == "%%" {
+        return Some(KlcCell::Ligature);
+    }
+
+    let (value, dead) = match cell.strip_suffix('@') {
+        Some(value) => (value, true),
+        None => (cell, false),
+    };
+
+    let mut chars = value.chars();
+
+    let character = match (chars.next(), chars.next()) {
+        (Some(character), None) => character,
+        _ if value.len() == 4 => parse_hex(value)?,
+        _ => return None,
+    };
+
+    if character.is_control() {
+        return Some(KlcCell::Empty);
+    }
+
+    if dead {
+        Some(KlcCell::DeadKey(character))
+    } else {
+        Some(KlcCell::Character(character))
+    }
+}
+
+fn keys_click_for_shift_state(key: KeyboardKey, shift_state: u8) -> Option<KeysClick> {
+    // Kana and other IME shift states can not be reached with modifiers alone.
+    if shift_state & !(KLC_SHIFT | KLC_CONTROL | KLC_ALT) != 0 {
+        return None;
+    }
+
+    let mut keys_click = KeysClick::new(key);
+
+    // Windows treats Ctrl+Alt as AltGr, so there is no need to send right alt explicitly.
+    if shift_state & KLC_CONTROL != 0 {
+        keys_click = keys_click.add_modifier(KeyboardModifierKey::LeftControl);
+    }
+
+    if shift_state & KLC_SHIFT != 0 {
+        keys_click = keys_click.add_modifier(KeyboardModifierKey::LeftShift);
+    }
+
+    if shift_state & KLC_ALT != 0 {
+        keys_click = keys_click.add_modifier(KeyboardModifierKey::LeftAlt);
+    }
+
+    Some(keys_click)
+}
+
+// Keeps the keystroke with the least modifiers when several keys produce the same character.
+fn insert_keys_click(
+    characters: &mut HashMap<char, Vec<KeysClick>>,
+    character: char,
+    keys_click: KeysClick,
+) {
+    let replace = match characters.get(&character) {
+        Some(existing) => {
+            existing[0].modifiers().bits().count_ones() > keys_click.modifiers().bits().count_ones()
+        }
+        None => true,
+    };
+
+    if replace {
+        characters.insert(character, vec![keys_click]);
+    }
+}
+
+const US_CHARACTERS: [(char, KeyboardKey, bool); 97] = [
+    ('a', KeyboardKey::A, false),
+    ('b', KeyboardKey::B, false),
+    ('c', KeyboardKey::C, false),
+    ('d', KeyboardKey::D, false),
+    ('e', KeyboardKey::E, false),
+    ('f', KeyboardKey::F, false),
+    ('g', KeyboardKey::G, false),
+    ('h', KeyboardKey::H, false),
+    ('i', KeyboardKey::I, false),
+    ('j', KeyboardKey::J, false),
+    ('k', KeyboardKey::K, false),
+    ('l', KeyboardKey::L, false),
+    ('m', KeyboardKey::M, false),
+    ('n', KeyboardKey::N, false),
+    ('o', KeyboardKey::O, false),
+    ('p', KeyboardKey::P, false),
+    ('q', KeyboardKey::Q, false),
+    ('r', KeyboardKey::R, false),
+    ('s', KeyboardKey::S, false),
+    ('t', KeyboardKey::T, false),
+    ('u', KeyboardKey::U, false),
+    ('v', KeyboardKey::V, false),
+    ('w', KeyboardKey::W, false),
+    ('x', KeyboardKey::X, false),
+    ('y', KeyboardKey::Y, false),
+    ('z', KeyboardKey::Z, false),
+    ('A', KeyboardKey::A, true),
+    ('B', KeyboardKey::B, true),
+    ('C', KeyboardKey::C, true),
+    ('D', KeyboardKey::D, true),
+    ('E', KeyboardKey::E, true),
+    ('F', KeyboardKey::F, true),
+    ('G', KeyboardKey::G, true),
+    ('H', KeyboardKey::H, true),
+    ('I', KeyboardKey::I, true),
+    ('J', KeyboardKey::J, true),
+    ('K', KeyboardKey::K, true),
+    ('L', KeyboardKey::L, true),
+    ('M', KeyboardKey::M, true),
+    ('N', KeyboardKey::N, true),
+    ('O', KeyboardKey::O, true),
+    ('P', KeyboardKey::P, true),
+    ('Q', KeyboardKey::Q, true),
+    ('R', KeyboardKey::R, true),
+    ('S', KeyboardKey::S, true),
+    ('T', KeyboardKey::T, true),
+    ('U', KeyboardKey::U, true),
+    ('V', KeyboardKey::V, true),
+    ('W', KeyboardKey::W, true),
+    ('X', KeyboardKey::X, true),
+    ('Y', KeyboardKey::Y, true),
+    ('Z', KeyboardKey::Z, true),
+    ('1', KeyboardKey::Num1, false),
+    ('2', KeyboardKey::Num2, false),
+    ('3', KeyboardKey::Num3, false),
+    ('4', KeyboardKey::Num4, false),
+    ('5', KeyboardKey::Num5, false),
+    ('6', KeyboardKey::Num6, false),
+    ('7', KeyboardKey::Num7, false),
+    ('8', KeyboardKey::Num8, false),
+    ('9', KeyboardKey::Num9, false),
+    ('0', KeyboardKey::Num0, false),
+    ('!', KeyboardKey::Num1, true),
+    ('@', KeyboardKey::Num2, true),
+    ('#', KeyboardKey::Num3, true),
+    ('$', KeyboardKey::Num4, true),
+    ('%', KeyboardKey::Num5, true),
+    ('^', KeyboardKey::Num6, true),
+    ('&', KeyboardKey::Num7, true),
+    ('*', KeyboardKey::Num8, true),
+    ('(', KeyboardKey::Num9, true),
+    (')', KeyboardKey::Num0, true),
+    ('-', KeyboardKey::Minus, false),
+    ('_', KeyboardKey::Minus, true),
+    ('=', KeyboardKey::Equal, false),
+    ('+', KeyboardKey::Equal, true),
+    ('[', KeyboardKey::LeftBracket, false),
+    ('{', KeyboardKey::LeftBracket, true),
+    (']', KeyboardKey::RightBracket, false),
+    ('}', KeyboardKey::RightBracket, true),
+    ('\\', KeyboardKey::Backslash, false),
+    ('|', KeyboardKey::Backslash, true),
+    (';', KeyboardKey::Semicolon, false),
+    (':', KeyboardKey::Semicolon, true),
+    ('\'', KeyboardKey::Quote, false),
+    ('"', KeyboardKey::Quote, true),
+    ('`', KeyboardKey::Grave, false),
+    ('~', KeyboardKey::Grave, true),
+    (',', KeyboardKey::Comma, false),
+    ('<', KeyboardKey::Comma, true),
+    ('.', KeyboardKey::Period, false),
+    ('>', KeyboardKey::Period, true),
+    ('/', KeyboardKey::Slash, false),
+    ('?', KeyboardKey::Slash, true),
+    (' ', KeyboardKey::Space, false),
+    ('\n', KeyboardKey::Enter, false),
+    ('\t', KeyboardKey::Tab, false),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
+    use crate::layout::{KeyboardLayout, KlcError};
+
+    const GERMAN_KLC: &str = include_str!("../tests/layouts/german.klc");
+
+    #[test]
+    fn test_us_layout() {
+        let layout = KeyboardLayout::us();
+
+        let shift_a = KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::LeftShift);
+
+        assert_eq!(
+            layout.keys_for('a'),
+            Some(&[KeysClick::new(KeyboardKey::A)][..])
+        );
+        assert_eq!(layout.keys_for('A'), Some(&[shift_a][..]));
+        assert_eq!(
+            layout.keys_for('\n'),
+            Some(&[KeysClick::new(KeyboardKey::Enter)][..])
+        );
+        assert_eq!(layout.keys_for('ä'), None);
+    }
+
+    #[test]
+    fn test_caps_lock_keys() {
+        let us = KeyboardLayout::us();
+        let german = KeyboardLayout::from_klc(GERMAN_KLC).unwrap();
+
+        assert!(us.is_caps_lock_key(KeyboardKey::A));
+        assert!(!us.is_caps_lock_key(KeyboardKey::Num1));
+        assert!(german.is_caps_lock_key(KeyboardKey::Y));
+        assert!(german.is_caps_lock_key(KeyboardKey::LeftBracket));
+        assert!(!german.is_caps_lock_key(KeyboardKey::Minus));
+    }
+
+    #[test]
+    fn test_klc_header() {
+        let layout = KeyboardLayout::from_klc(GERMAN_KLC).unwrap();
+
+        assert_eq!(layout.name(), "GERMAN");
+        assert_eq!(layout.description(), "German (sample)");
+    }
+
+    #[test]
+    fn test_klc_shift_states() {
+        let layout = KeyboardLayout::from_klc(GERMAN_KLC).unwrap();
+
+        let z = KeysClick::new(KeyboardKey::Y);
+        let shift_z = KeysClick::new(KeyboardKey::Y).add_modifier(KeyboardModifierKey::LeftShift);
+        let at = KeysClick::new(KeyboardKey::Q)
+            .add_modifier(KeyboardModifierKey::LeftControl)
+            .add_modifier(KeyboardModifierKey::LeftAlt);
+
+        assert_eq!(layout.keys_for('z'), Some(&[z][..]));
+        assert_eq!(layout.keys_for('Z'), Some(&[shift_z][..]));
+        assert_eq!(layout.keys_for('@'), Some(&[at][..]));
+        assert_eq!(
+            layout.keys_for('ß'),
+            Some(&[KeysClick::new(KeyboardKey::Minus)][..])
+        );
+        assert_eq!(
+            layout.keys_for('ü'),
+            Some(&[KeysClick::new(KeyboardKey::LeftBracket)][..])
+        );
+    }
+
+    #[test]
+    fn test_klc_prefers_fewer_modifiers() {
+        // '!' comes first with shift, '"' comes first without.
+        let layout = KeyboardLayout::from_klc(
+            "SHIFTSTATE\n0\n1\nLAYOUT\n02\t1\t0\t0022\t0021\n03\t2\t0\t0021\t0022\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            layout.keys_for('!'),
+            Some(&[KeysClick::new(KeyboardKey::Num2)][..])
+        );
+        assert_eq!(
+            layout.keys_for('"'),
+            Some(&[KeysClick::new(KeyboardKey::Num1)][..])
+        );
+    }
+
+    #[test]
+    fn test_klc_dead_keys() {
+        let layout = KeyboardLayout::from_klc(GERMAN_KLC).unwrap();
+
+        let circumflex = KeysClick::new(KeyboardKey::Grave);
+        let acute = KeysClick::new(KeyboardKey::Equal);
+
+        assert_eq!(
+            layout.keys_for('â'),
+            Some(&[circumflex, KeysClick::new(KeyboardKey::A)][..])
+        );
+        assert_eq!(
+            layout.keys_for('^'),
+            Some(&[circumflex, KeysClick::new(KeyboardKey::Space)][..])
+        );
+        assert_eq!(
+            layout.keys_for('é'),
+            Some(&[acute, KeysClick::new(KeyboardKey::E)][..])
+        );
+    }
+
+    #[test]
+    fn test_klc_ligatures() {
+        let klc = "KBD\tLIG\t\"Ligature\"\n\
+                   SHIFTSTATE\n\
+                   0\n\
+                   1\n\
+                   LAYOUT\n\
+                   1e\tA\t1\ta\t%%\n\
+                   LIGATURE\n\
+                   A\t1\t0061\t0065\n\
+                   ENDKBD\n";
+
+        let layout = KeyboardLayout::from_klc(klc).unwrap();
+
+        let shift_a = KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::LeftShift);
+
+        assert_eq!(layout.keys_for_ligature("ae"), Some(&shift_a));
+        assert_eq!(layout.keys_for('A'), None);
+    }
+
+    #[test]
+    fn test_klc_utf16() {
+        let mut bytes = vec![0xFF, 0xFE];
+
+        for unit in GERMAN_KLC.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        let layout = KeyboardLayout::from_klc_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            layout.keys_for('ö'),
+            Some(&[KeysClick::new(KeyboardKey::Semicolon)][..])
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc_bytes(&[0xFF, 0xFE, 0x00]).unwrap_err(),
+            KlcError::InvalidEncoding
+        );
+    }
+
+    #[test]
+    fn test_klc_errors() {
+        assert_eq!(
+            KeyboardLayout::from_klc("KBD\tX\t\"X\"\nLAYOUT\n").unwrap_err(),
+            KlcError::MissingShiftStates
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc("SHIFTSTATE\n0\n").unwrap_err(),
+            KlcError::MissingLayout
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc("SHIFTSTATE\nshift\n").unwrap_err(),
+            KlcError::InvalidShiftState(2)
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc("SHIFTSTATE\n0\nLAYOUT\nzz\tA\t1\ta\n").unwrap_err(),
+            KlcError::InvalidLayoutRow(4)
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc("SHIFTSTATE\n0\nLAYOUT\n1e\tA\t1\tabc\n").unwrap_err(),
+            KlcError::InvalidCharacter(4)
+        );
+        assert_eq!(
+            KeyboardLayout::from_klc("SHIFTSTATE\n0\nLAYOUT\nDEADKEY\tx\n").unwrap_err(),
+            KlcError::InvalidDeadKey(4)
+        );
+    }
+}
@@ -1,9 +1,21 @@
+pub mod ahk;
+pub mod clock;
+pub mod compiled;
 pub mod device;
 pub mod display;
+pub mod human;
 pub mod keyboard;
+pub mod keycode;
+pub mod layout;
+pub mod lock;
 pub mod mouse;
+pub mod sequence;
+pub mod typing;
+pub mod xdotool;
 
 #[cfg(feature = "serde")]
 mod de;
+#[cfg(feature = "schemars")]
+mod schema;
 #[cfg(feature = "serde")]
 mod ser;
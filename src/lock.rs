@@ -0,0 +1,64 @@
+#[cfg(windows)]
+use winapi::um::winuser::{GetKeyState, VK_CAPITAL, VK_NUMLOCK, VK_SCROLL};
+
+/// Whether the lock keys of the target are toggled on.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct LockState {
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+}
+
+/// Source of the lock key state, replaceable to test without a real keyboard.
+pub trait LockStateProvider {
+    fn lock_state(&self) -> LockState;
+}
+
+/// A fixed lock state.
+impl LockStateProvider for LockState {
+    fn lock_state(&self) -> LockState {
+        *self
+    }
+}
+
+/// Lock key state as seen by Windows.
+#[cfg(windows)]
+pub struct SystemLockState;
+
+#[cfg(windows)]
+impl LockStateProvider for SystemLockState {
+    fn lock_state(&self) -> LockState {
+        // The lowest bit of GetKeyState tells whether the key is toggled.
+        let toggled = |key| unsafe { GetKeyState(key) } & 1 != 0;
+
+        LockState {
+            caps_lock: toggled(VK_CAPITAL),
+            num_lock: toggled(VK_NUMLOCK),
+            scroll_lock: toggled(VK_SCROLL),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::lock::{LockState, LockStateProvider};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct FakeLockState {
+        state: Rc<Cell<LockState>>,
+    }
+
+    impl FakeLockState {
+        pub(crate) fn set(&self, state: LockState) {
+            self.state.set(state);
+        }
+    }
+
+    impl LockStateProvider for FakeLockState {
+        fn lock_state(&self) -> LockState {
+            self.state.get()
+        }
+    }
+}
@@ -1,15 +1,34 @@
-use crate::device::{find_device, Device, DeviceError, CONTROL_REPORT_ID};
+#[cfg(windows)]
+use crate::device::{find_device, DeviceError};
+use crate::device::{Transport, CONTROL_REPORT_ID};
 use crate::display::DisplayInfo;
-use std::mem::{size_of, MaybeUninit};
-use winapi::ctypes::c_void;
+use std::fmt::{Display, Error, Formatter};
+use std::mem::size_of;
+#[cfg(windows)]
+use std::mem::MaybeUninit;
+#[cfg(windows)]
 use winapi::shared::windef::POINT;
+#[cfg(windows)]
 use winapi::um::winuser::GetCursorPos;
 
 const MOUSE_REPORT_ID: u8 = 0x03;
 const MOUSE_REPORT_SIZE: u8 = size_of::<MouseReport>() as u8;
 const MOUSE_COORDINATES_RANGE: u32 = 32_768;
 
+#[derive(Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
 pub struct MouseClick {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::ser::serialize_mouse_buttons",
+            deserialize_with = "crate::de::deserialize_mouse_buttons"
+        )
+    )]
     buttons: u8,
     x: u16,
     y: u16,
@@ -35,12 +54,7 @@ impl MouseClick {
     }
 
     pub fn set_button(mut self, button: MouseButton) -> Self {
-        self.buttons |= match button {
-            MouseButton::Left => 1,
-            MouseButton::Right => 2,
-            MouseButton::Middle => 4,
-        };
-
+        self.buttons |= button.mask();
         self
     }
 
@@ -53,16 +67,70 @@ impl MouseClick {
         self.display_index = Some(display_index);
         self
     }
+
+    pub fn has_button(&self, button: MouseButton) -> bool {
+        self.buttons & button.mask() != 0
+    }
+
+    pub fn buttons(&self) -> Vec<MouseButton> {
+        MOUSE_BUTTONS
+            .iter()
+            .filter(|b| self.has_button(**b))
+            .cloned()
+            .collect()
+    }
+
+    pub fn position(&self) -> (u16, u16) {
+        (self.x, self.y)
+    }
+
+    pub fn wheel_position(&self) -> u8 {
+        self.wheel_position
+    }
+
+    pub fn display_index(&self) -> Option<usize> {
+        self.display_index
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+pub(crate) const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl MouseButton {
+    pub(crate) fn mask(&self) -> u8 {
+        match self {
+            MouseButton::Left => 1,
+            MouseButton::Right => 2,
+            MouseButton::Middle => 4,
+        }
+    }
+}
+
+impl Display for MouseButton {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct MousePosition {
     pub display_index: usize,
     pub x: u16,
@@ -81,8 +149,29 @@ struct MouseReport {
     wheel_position: u8,
 }
 
+impl MouseReport {
+    fn to_bytes(&self) -> [u8; MOUSE_REPORT_SIZE as usize] {
+        let x = self.x.to_le_bytes();
+        let y = self.y.to_le_bytes();
+
+        // Trailing byte is the struct padding counted in the report length.
+        [
+            self.control_report_id,
+            self.report_length,
+            self.report_id,
+            self.buttons,
+            x[0],
+            x[1],
+            y[0],
+            y[1],
+            self.wheel_position,
+            0,
+        ]
+    }
+}
+
 pub struct Mouse {
-    device: Device,
+    device: Box<dyn Transport>,
     primary_display_info: DisplayInfo,
     displays_info: Vec<DisplayInfo>,
     mouse_x_coord_per_pixel: f64,
@@ -90,9 +179,18 @@ pub struct Mouse {
 }
 
 impl Mouse {
+    #[cfg(windows)]
     pub fn init(displays_info: Vec<DisplayInfo>) -> Result<Self, DeviceError> {
         let device = find_device()?;
 
+        Ok(Mouse::from_transport(device, displays_info))
+    }
+
+    /// Creates a mouse sending its reports somewhere else than the vmulti device.
+    pub fn from_transport<T: Transport + 'static>(
+        transport: T,
+        displays_info: Vec<DisplayInfo>,
+    ) -> Self {
         // By default we use primary display.
         let primary_display_info = *displays_info.iter().find(|d| d.primary).unwrap();
 
@@ -105,16 +203,21 @@ impl Mouse {
         let mouse_x_coord_per_pixel = MOUSE_COORDINATES_RANGE as f64 / total_width as f64;
         let mouse_y_coord_per_pixel = MOUSE_COORDINATES_RANGE as f64 / total_height as f64;
 
-        Ok(Mouse {
-            device,
+        Mouse {
+            device: Box::new(transport),
             primary_display_info,
             displays_info,
             mouse_x_coord_per_pixel,
             mouse_y_coord_per_pixel,
-        })
+        }
     }
 
     pub fn send_click(&self, click: MouseClick) -> bool {
+        self.device.send_report(&self.click_report(&click))
+    }
+
+    // Encodes the click as a report, positioned relatively to its display.
+    pub(crate) fn click_report(&self, click: &MouseClick) -> [u8; MOUSE_REPORT_SIZE as usize] {
         let display_info = match click.display_index {
             Some(display_index) => self
                 .displays_info
@@ -142,7 +245,7 @@ impl Mouse {
 
         let buttons = click.buttons;
 
-        let mut report = MouseReport {
+        let report = MouseReport {
             control_report_id: CONTROL_REPORT_ID,
             report_length: MOUSE_REPORT_SIZE,
             report_id: MOUSE_REPORT_ID,
@@ -152,10 +255,10 @@ impl Mouse {
             wheel_position: click.wheel_position,
         };
 
-        self.device
-            .send_report(&mut report as *mut _ as *mut c_void)
+        report.to_bytes()
     }
 
+    #[cfg(windows)]
     pub fn get_mouse_position(&self) -> MousePosition {
         let mut maybe_point = MaybeUninit::<POINT>::uninit();
 
@@ -187,3 +290,27 @@ impl Mouse {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::device::testing::RecordingTransport;
+    use crate::display::{DisplayInfo, DisplayPosition};
+    use crate::mouse::Mouse;
+
+    /// Mouse on a single 1920x1080 primary display.
+    pub(crate) fn mouse(transport: RecordingTransport) -> Mouse {
+        let display_info = DisplayInfo {
+            primary: true,
+            position: DisplayPosition {
+                left: 0,
+                top: 0,
+                right: 1920,
+                bottom: 1080,
+            },
+            width: 1920,
+            height: 1080,
+        };
+
+        Mouse::from_transport(transport, vec![display_info])
+    }
+}
@@ -0,0 +1,353 @@
+use crate::display::{DisplayInfo, DisplayPosition};
+use crate::keyboard::{
+    KeyboardKey, KeyboardModifierKey, KeysClick, EMPTY_KEYS_CLICK, KEY_NAMES, MODIFIER_NAMES,
+};
+use crate::mouse::{MouseButton, MouseClick, MousePosition, MOUSE_BUTTONS};
+use crate::sequence::KeySequence;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+
+// Schemas are built from the name tables of the parsers, so that anything they accept validates.
+// They are looser than the parsers though, which alone reject duplicate or too many keys.
+
+fn string_schema(description: &str) -> SchemaObject {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    };
+
+    schema.metadata().description = Some(description.to_owned());
+    schema
+}
+
+fn enum_schema<'a, I: Iterator<Item = &'a str>>(description: &str, names: I) -> Schema {
+    let mut schema = string_schema(description);
+
+    schema.enum_values = Some(names.map(Into::into).collect());
+    schema.into()
+}
+
+// Names matched whatever the case of their letters, as the key parsers do.
+fn names_schema<'a, I: Iterator<Item = &'a str>>(description: &str, names: I) -> Schema {
+    let mut schema = string_schema(description);
+    let names: Vec<String> = names.map(case_insensitive).collect();
+
+    schema.string().pattern = Some(format!("^(?:{})$", names.join("|")));
+    schema.into()
+}
+
+// Matches the name whatever the case of its letters, as the parsers do.
+fn case_insensitive(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphabetic() => {
+                format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase())
+            }
+            c if c.is_ascii_alphanumeric() || c == '-' => c.to_string(),
+            c => format!("\\{}", c),
+        })
+        .collect()
+}
+
+/// Regular expression of the keys click grammar, such as "ctrl + shift + esc", "ctrl++" or
+/// "none".
+pub(crate) fn keys_click_pattern() -> String {
+    let mut names: Vec<&str> = KEY_NAMES
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(MODIFIER_NAMES.iter().map(|(name, _)| *name))
+        .collect();
+
+    // Longest names first, so that "f1" does not shadow "f10".
+    names.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+    let names: Vec<String> = names.into_iter().map(case_insensitive).collect();
+    let key = format!("(?:{}|\\+)", names.join("|"));
+
+    format!(
+        "^\\s*(?:{empty}\\s*|{key}\\s*(?:\\+\\s*{key}\\s*)*(?:\\+\\s*)?)$",
+        empty = case_insensitive(EMPTY_KEYS_CLICK),
+        key = key
+    )
+}
+
+fn object_schema(description: &str, properties: Vec<(&str, Schema)>, required: bool) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        ..Default::default()
+    };
+
+    schema.metadata().description = Some(description.to_owned());
+
+    let object = schema.object();
+    object.additional_properties = Some(Box::new(false.into()));
+
+    for (name, property) in properties {
+        if required {
+            object.required.insert(name.to_owned());
+        }
+
+        object.properties.insert(name.to_owned(), property);
+    }
+
+    schema.into()
+}
+
+impl JsonSchema for KeyboardKey {
+    fn schema_name() -> String {
+        "KeyboardKey".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        names_schema(
+            "Name of a keyboard key, case insensitive.",
+            KEY_NAMES.iter().map(|(n, _)| *n),
+        )
+    }
+}
+
+impl JsonSchema for KeyboardModifierKey {
+    fn schema_name() -> String {
+        "KeyboardModifierKey".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        names_schema(
+            "Name of a keyboard modifier, case insensitive.",
+            MODIFIER_NAMES.iter().map(|(n, _)| *n),
+        )
+    }
+}
+
+impl JsonSchema for KeysClick {
+    fn schema_name() -> String {
+        "KeysClick".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let mut schema = string_schema(
+            "Modifiers and keys separated with +, such as \"ctrl+shift+esc\", or \"none\" \
+             when nothing is pressed. Names are case insensitive, the plus key is written \
+             \"plus\" or \"+\".",
+        );
+
+        schema.string().pattern = Some(keys_click_pattern());
+        schema.into()
+    }
+}
+
+impl JsonSchema for KeySequence {
+    fn schema_name() -> String {
+        "KeySequence".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "Key actions separated with commas: keys clicks such as \"ctrl+c\", repeats such \
+             as \"enter x3\", waits such as \"wait 200ms\" or \"wait 2s\", holds such as \
+             \"hold shift { a, b }\" and presses and releases such as \"press alt\" or \
+             \"release alt\". An empty string is the empty sequence. Only checked to be a \
+             string, the grammar nests holds which a pattern can not express.",
+        )
+        .into()
+    }
+}
+
+impl JsonSchema for MouseButton {
+    fn schema_name() -> String {
+        "MouseButton".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let names: Vec<String> = MOUSE_BUTTONS.iter().map(|b| b.to_string()).collect();
+
+        enum_schema("Name of a mouse button.", names.iter().map(|n| n.as_str()))
+    }
+}
+
+impl JsonSchema for MouseClick {
+    fn schema_name() -> String {
+        "MouseClick".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut buttons = SchemaObject {
+            instance_type: Some(InstanceType::Array.into()),
+            ..Default::default()
+        };
+
+        buttons.array().items = Some(gen.subschema_for::<MouseButton>().into());
+        buttons.array().unique_items = Some(true);
+
+        let properties = vec![
+            ("buttons", buttons.into()),
+            ("x", gen.subschema_for::<u16>()),
+            ("y", gen.subschema_for::<u16>()),
+            ("wheel_position", gen.subschema_for::<u8>()),
+            ("display_index", gen.subschema_for::<Option<usize>>()),
+        ];
+
+        object_schema(
+            "Mouse buttons, position and wheel, missing fields default to zero.",
+            properties,
+            false,
+        )
+    }
+}
+
+impl JsonSchema for MousePosition {
+    fn schema_name() -> String {
+        "MousePosition".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let properties = vec![
+            ("display_index", gen.subschema_for::<usize>()),
+            ("x", gen.subschema_for::<u16>()),
+            ("y", gen.subschema_for::<u16>()),
+        ];
+
+        object_schema("Position on a display.", properties, true)
+    }
+}
+
+impl JsonSchema for DisplayInfo {
+    fn schema_name() -> String {
+        "DisplayInfo".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let properties = vec![
+            ("primary", gen.subschema_for::<bool>()),
+            ("position", gen.subschema_for::<DisplayPosition>()),
+            ("width", gen.subschema_for::<u16>()),
+            ("height", gen.subschema_for::<u16>()),
+        ];
+
+        object_schema("Display bounds.", properties, true)
+    }
+}
+
+impl JsonSchema for DisplayPosition {
+    fn schema_name() -> String {
+        "DisplayPosition".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let properties = vec![
+            ("left", gen.subschema_for::<i32>()),
+            ("top", gen.subschema_for::<i32>()),
+            ("right", gen.subschema_for::<i32>()),
+            ("bottom", gen.subschema_for::<i32>()),
+        ];
+
+        object_schema("Display edges in virtual screen pixels.", properties, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick, KEY_NAMES, MODIFIER_NAMES};
+    use crate::mouse::MouseClick;
+    use crate::schema::keys_click_pattern;
+    use regex::Regex;
+    use schemars::schema::RootSchema;
+    use schemars::schema_for;
+    use serde_json::{json, to_value};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_keys_click_pattern() {
+        let pattern = Regex::new(&keys_click_pattern()).unwrap();
+
+        for input in [
+            "a",
+            "ctrl+c",
+            "Ctrl + Shift + Esc",
+            "left-ctrl+f10",
+            "+",
+            "ctrl++",
+            "ctrl + +",
+            "ctrl+++a",
+            "ctrl+",
+            "a +",
+            "CONTROL+plus",
+            "none",
+            " None ",
+        ]
+        .iter()
+        {
+            assert!(pattern.is_match(input), "{}", input);
+            assert!(KeysClick::try_from(*input).is_ok(), "{}", input);
+        }
+
+        for input in [
+            "",
+            "+a",
+            "ctrl++a",
+            "ctrll+c",
+            "a b",
+            "ctrl+-",
+            "none+a",
+            "ctrl+none",
+        ]
+        .iter()
+        {
+            assert!(!pattern.is_match(input), "{}", input);
+            assert!(KeysClick::try_from(*input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_keys_schema_matches_every_name() {
+        let pattern = |schema: RootSchema| {
+            let schema = to_value(schema).unwrap();
+            Regex::new(schema["pattern"].as_str().unwrap()).unwrap()
+        };
+
+        let keys = pattern(schema_for!(KeyboardKey));
+        let modifiers = pattern(schema_for!(KeyboardModifierKey));
+
+        for (name, key) in KEY_NAMES {
+            assert!(keys.is_match(name), "{}", name);
+            assert!(keys.is_match(&name.to_uppercase()), "{}", name);
+            assert_eq!(KeyboardKey::try_from(*name), Ok(*key));
+        }
+
+        for (name, _) in MODIFIER_NAMES {
+            assert!(modifiers.is_match(name), "{}", name);
+        }
+
+        for input in ["Return", "PAGE-UP", "f10"].iter() {
+            assert!(keys.is_match(input), "{}", input);
+            assert!(KeyboardKey::try_from(*input).is_ok(), "{}", input);
+        }
+
+        for input in ["", "entr", "f1 ", "a|b"].iter() {
+            assert!(!keys.is_match(input), "{}", input);
+            assert!(KeyboardKey::try_from(*input).is_err(), "{}", input);
+        }
+
+        assert!(modifiers.is_match("Left-Shift"));
+        assert!(KeyboardModifierKey::try_from("Left-Shift").is_ok());
+        assert!(!modifiers.is_match("a"));
+    }
+
+    #[test]
+    fn test_mouse_click_schema() {
+        let schema = to_value(schema_for!(MouseClick)).unwrap();
+
+        assert_eq!(schema["type"], json!("object"));
+        assert_eq!(schema["additionalProperties"], json!(false));
+        assert_eq!(
+            schema["properties"]["buttons"]["items"]["$ref"],
+            json!("#/definitions/MouseButton")
+        );
+        assert_eq!(
+            schema["definitions"]["MouseButton"]["enum"],
+            json!(["left", "right", "middle"])
+        );
+        assert!(schema.get("required").is_none());
+    }
+}
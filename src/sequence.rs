@@ -0,0 +1,552 @@
+use crate::keyboard::{KeysClick, ParseKeyError, ParseKeyErrorReason};
+use itertools::join;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::time::Duration;
+
+/// A step of a key sequence.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum KeyAction {
+    /// Taps the keys click, written as "ctrl+c".
+    Click(KeysClick),
+    /// Taps the keys click several times, written as "enter x3".
+    Repeat(KeysClick, u32),
+    /// Waits before the next action, written as "wait 200ms" or "wait 2s", or "wait 500us" and
+    /// "wait 20ns" below a millisecond.
+    Wait(Duration),
+    /// Keeps the keys click down while running the inner actions, written as "hold shift { a b }".
+    Hold(KeysClick, KeySequence),
+    /// Presses the keys click on top of the held keys, written as "press shift".
+    Press(KeysClick),
+    /// Releases the keys and modifiers of the keys click, written as "release shift".
+    Release(KeysClick),
+}
+
+/// A whole keyboard interaction, such as "ctrl+c, wait 200ms, ctrl+v, enter x3".
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct KeySequence {
+    actions: Vec<KeyAction>,
+}
+
+impl KeySequence {
+    pub fn new() -> Self {
+        KeySequence::default()
+    }
+
+    pub fn add_click(self, keys_click: KeysClick) -> Self {
+        self.add_action(KeyAction::Click(keys_click))
+    }
+
+    pub fn add_repeat(self, keys_click: KeysClick, count: u32) -> Self {
+        self.add_action(KeyAction::Repeat(keys_click, count))
+    }
+
+    pub fn add_wait(self, duration: Duration) -> Self {
+        self.add_action(KeyAction::Wait(duration))
+    }
+
+    pub fn add_hold(self, keys_click: KeysClick, sequence: KeySequence) -> Self {
+        self.add_action(KeyAction::Hold(keys_click, sequence))
+    }
+
+    pub fn add_press(self, keys_click: KeysClick) -> Self {
+        self.add_action(KeyAction::Press(keys_click))
+    }
+
+    pub fn add_release(self, keys_click: KeysClick) -> Self {
+        self.add_action(KeyAction::Release(keys_click))
+    }
+
+    /// Adds the action, leaving out repeats of zero times which do nothing.
+    pub fn add_action(mut self, action: KeyAction) -> Self {
+        if let KeyAction::Repeat(_, 0) = action {
+            return self;
+        }
+
+        self.actions.push(action);
+        self
+    }
+
+    pub fn actions(&self) -> &[KeyAction] {
+        &self.actions
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Token<'a> {
+    Word(&'a str),
+    Comma,
+    OpenBrace,
+    CloseBrace,
+}
+
+impl Token<'_> {
+    fn text(&self) -> &str {
+        match self {
+            Token::Word(word) => word,
+            Token::Comma => ",",
+            Token::OpenBrace => "{",
+            Token::CloseBrace => "}",
+        }
+    }
+}
+
+// Tokens with their start offset in the input.
+fn tokenize(input: &str) -> Vec<(Token<'_>, usize)> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+
+    for (index, c) in input.char_indices() {
+        let token = match c {
+            ',' => Some(Token::Comma),
+            '{' => Some(Token::OpenBrace),
+            '}' => Some(Token::CloseBrace),
+            c if c.is_whitespace() => None,
+            _ => {
+                word_start.get_or_insert(index);
+                continue;
+            }
+        };
+
+        if let Some(start) = word_start.take() {
+            push_word(&mut tokens, input, start, index);
+        }
+
+        if let Some(token) = token {
+            tokens.push((token, index));
+        }
+    }
+
+    if let Some(start) = word_start {
+        push_word(&mut tokens, input, start, input.len());
+    }
+
+    tokens
+}
+
+// Words next to a plus sign are joined, so that "ctrl + c" stays one word.
+fn push_word<'a>(tokens: &mut Vec<(Token<'a>, usize)>, input: &'a str, start: usize, end: usize) {
+    let word = &input[start..end];
+
+    if let Some((Token::Word(previous), previous_start)) = tokens.last().cloned() {
+        if previous.ends_with('+') || word.starts_with('+') {
+            tokens.pop();
+            tokens.push((Token::Word(&input[previous_start..end]), previous_start));
+            return;
+        }
+    }
+
+    tokens.push((Token::Word(word), start));
+}
+
+struct SequenceParser<'a> {
+    tokens: Vec<(Token<'a>, usize)>,
+    position: usize,
+    end: usize,
+}
+
+impl<'a> SequenceParser<'a> {
+    fn next(&mut self) -> Option<(Token<'a>, usize)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<(Token<'a>, usize)> {
+        self.tokens.get(self.position).cloned()
+    }
+
+    // Error pointing at the given token, or at the end of the input when there is none.
+    fn error(
+        &self,
+        token: Option<(Token<'_>, usize)>,
+        reason: ParseKeyErrorReason,
+    ) -> ParseKeyError {
+        match token {
+            Some((token, start)) => ParseKeyError::new(token.text(), start, reason),
+            None => ParseKeyError::new("", self.end, reason),
+        }
+    }
+
+    fn parse_actions(&mut self, nested: bool) -> Result<KeySequence, ParseKeyError> {
+        let mut sequence = KeySequence::new();
+
+        loop {
+            let (word, start) = match self.next() {
+                Some((Token::Word(word), start)) => (word, start),
+                Some((Token::Comma, _)) => continue,
+                Some((Token::CloseBrace, _)) if nested => return Ok(sequence),
+                token @ Some(_) => {
+                    return Err(self.error(token, ParseKeyErrorReason::UnexpectedBrace))
+                }
+                None if nested => {
+                    return Err(self.error(None, ParseKeyErrorReason::MissingClosingBrace))
+                }
+                None => return Ok(sequence),
+            };
+
+            let action = match word.to_lowercase().as_str() {
+                "wait" => match self.next() {
+                    Some((Token::Word(duration), start)) => {
+                        KeyAction::Wait(parse_duration(duration, start)?)
+                    }
+                    _ => {
+                        let reason = ParseKeyErrorReason::MissingDuration;
+                        return Err(ParseKeyError::new(word, start, reason));
+                    }
+                },
+                keyword @ "press" | keyword @ "release" => {
+                    let keys_click = match self.next() {
+                        Some((Token::Word(keys), start)) => parse_keys_click(keys, start)?,
+                        _ => {
+                            let reason = ParseKeyErrorReason::MissingKeys;
+                            return Err(ParseKeyError::new(word, start, reason));
+                        }
+                    };
+
+                    if keyword == "press" {
+                        KeyAction::Press(keys_click)
+                    } else {
+                        KeyAction::Release(keys_click)
+                    }
+                }
+                "hold" => {
+                    let keys_click = match self.next() {
+                        Some((Token::Word(keys), start)) => parse_keys_click(keys, start)?,
+                        _ => {
+                            let reason = ParseKeyErrorReason::MissingKeys;
+                            return Err(ParseKeyError::new(word, start, reason));
+                        }
+                    };
+
+                    match self.next() {
+                        Some((Token::OpenBrace, _)) => {}
+                        token => {
+                            let reason = ParseKeyErrorReason::MissingOpeningBrace;
+                            return Err(self.error(token, reason));
+                        }
+                    }
+
+                    KeyAction::Hold(keys_click, self.parse_actions(true)?)
+                }
+                _ => {
+                    let keys_click = parse_keys_click(word, start)?;
+
+                    match self.peek().and_then(repeat_count) {
+                        Some(count) => {
+                            self.position += 1;
+                            KeyAction::Repeat(keys_click, count?)
+                        }
+                        None => KeyAction::Click(keys_click),
+                    }
+                }
+            };
+
+            sequence = sequence.add_action(action);
+        }
+    }
+}
+
+fn parse_keys_click(word: &str, start: usize) -> Result<KeysClick, ParseKeyError> {
+    KeysClick::try_from(word).map_err(|error| error.offset(start))
+}
+
+fn repeat_count((token, start): (Token<'_>, usize)) -> Option<Result<u32, ParseKeyError>> {
+    let word = match token {
+        Token::Word(word) if word.len() > 1 && word.starts_with(&['x', 'X'][..]) => word,
+        _ => return None,
+    };
+
+    if !word[1..].chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    match word[1..].parse::<u32>() {
+        Ok(count) if count > 0 => Some(Ok(count)),
+        _ => {
+            let reason = ParseKeyErrorReason::InvalidRepeatCount;
+            Some(Err(ParseKeyError::new(word, start, reason)))
+        }
+    }
+}
+
+// Units of whole durations, in nanoseconds, seconds accepting a fraction.
+const DURATION_UNITS: [(&str, u64); 3] = [("ms", 1_000_000), ("us", 1_000), ("ns", 1)];
+
+fn parse_duration(word: &str, start: usize) -> Result<Duration, ParseKeyError> {
+    let value = word.to_lowercase();
+    let error = || ParseKeyError::new(word, start, ParseKeyErrorReason::InvalidDuration);
+
+    for (unit, nanos) in DURATION_UNITS.iter() {
+        if let Some(count) = value.strip_suffix(unit) {
+            return count
+                .parse::<u64>()
+                .ok()
+                .and_then(|count| count.checked_mul(*nanos))
+                .map(Duration::from_nanos)
+                .ok_or_else(error);
+        }
+    }
+
+    match value.strip_suffix('s').map(|s| s.parse::<f64>()) {
+        Some(Ok(seconds)) if seconds.is_finite() && seconds >= 0.0 => {
+            Ok(Duration::from_millis((seconds * 1000.0).round() as u64))
+        }
+        _ => Err(error()),
+    }
+}
+
+impl TryFrom<&str> for KeySequence {
+    type Error = ParseKeyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        SequenceParser {
+            tokens: tokenize(value),
+            position: 0,
+            end: value.len(),
+        }
+        .parse_actions(false)
+    }
+}
+
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            KeyAction::Click(keys_click) => write!(f, "{}", keys_click),
+            KeyAction::Repeat(keys_click, count) => write!(f, "{} x{}", keys_click, count),
+            KeyAction::Wait(duration) => {
+                let nanos = duration.as_nanos();
+
+                if nanos > 0 && nanos % 1_000_000_000 == 0 {
+                    write!(f, "wait {}s", nanos / 1_000_000_000)
+                } else if nanos % 1_000_000 == 0 {
+                    write!(f, "wait {}ms", nanos / 1_000_000)
+                } else if nanos % 1_000 == 0 {
+                    write!(f, "wait {}us", nanos / 1_000)
+                } else {
+                    write!(f, "wait {}ns", nanos)
+                }
+            }
+            KeyAction::Hold(keys_click, sequence) => {
+                write!(f, "hold {} {{ {} }}", keys_click, sequence)
+            }
+            KeyAction::Press(keys_click) => write!(f, "press {}", keys_click),
+            KeyAction::Release(keys_click) => write!(f, "release {}", keys_click),
+        }
+    }
+}
+
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", join(&self.actions, ", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick, ParseKeyErrorReason};
+    use crate::sequence::KeySequence;
+    use std::convert::TryFrom;
+    use std::time::Duration;
+
+    fn ctrl(key: KeyboardKey) -> KeysClick {
+        KeysClick::new(key).add_modifier(KeyboardModifierKey::LeftControl)
+    }
+
+    #[test]
+    fn test_sequence_parse() {
+        let sequence = KeySequence::try_from(
+            "left-ctrl+c, wait 200ms, left-ctrl+v, enter x3, hold left-shift { a b c }",
+        )
+        .unwrap();
+
+        let held = KeySequence::new()
+            .add_click(KeysClick::new(KeyboardKey::A))
+            .add_click(KeysClick::new(KeyboardKey::B))
+            .add_click(KeysClick::new(KeyboardKey::C));
+
+        let expected = KeySequence::new()
+            .add_click(ctrl(KeyboardKey::C))
+            .add_wait(Duration::from_millis(200))
+            .add_click(ctrl(KeyboardKey::V))
+            .add_repeat(KeysClick::new(KeyboardKey::Enter), 3)
+            .add_hold(
+                KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift),
+                held,
+            );
+
+        assert_eq!(sequence, expected);
+    }
+
+    #[test]
+    fn test_sequence_parse_press_release() {
+        let sequence = KeySequence::try_from("press alt, tab x2, release alt, PRESS a").unwrap();
+        let alt = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftAlt);
+
+        let expected = KeySequence::new()
+            .add_press(alt)
+            .add_repeat(KeysClick::new(KeyboardKey::Tab), 2)
+            .add_release(alt)
+            .add_press(KeysClick::new(KeyboardKey::A));
+
+        assert_eq!(sequence, expected);
+    }
+
+    #[test]
+    fn test_sequence_parse_durations() {
+        let sequence = KeySequence::try_from("wait 2s, wait 0.5s, wait 15MS").unwrap();
+
+        let expected = KeySequence::new()
+            .add_wait(Duration::from_secs(2))
+            .add_wait(Duration::from_millis(500))
+            .add_wait(Duration::from_millis(15));
+
+        assert_eq!(sequence, expected);
+    }
+
+    #[test]
+    fn test_sequence_parse_nested() {
+        let sequence =
+            KeySequence::try_from("hold left-ctrl{hold left-shift{tab x2},wait 1s}").unwrap();
+
+        let inner = KeySequence::new().add_repeat(KeysClick::new(KeyboardKey::Tab), 2);
+        let outer = KeySequence::new()
+            .add_hold(
+                KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift),
+                inner,
+            )
+            .add_wait(Duration::from_secs(1));
+
+        let expected = KeySequence::new().add_hold(
+            KeysClick::empty().add_modifier(KeyboardModifierKey::LeftControl),
+            outer,
+        );
+
+        assert_eq!(sequence, expected);
+    }
+
+    #[test]
+    fn test_sequence_parse_friendly_chords() {
+        let sequence =
+            KeySequence::try_from("Ctrl + Shift + Esc, ctrl ++, ctrl +, hold shift { a b }")
+                .unwrap();
+
+        let expected = KeySequence::new()
+            .add_click(
+                KeysClick::new(KeyboardKey::Esc)
+                    .add_modifier(KeyboardModifierKey::LeftControl)
+                    .add_modifier(KeyboardModifierKey::LeftShift),
+            )
+            .add_click(ctrl(KeyboardKey::KeypadPlus))
+            .add_click(ctrl(KeyboardKey::KeypadPlus))
+            .add_hold(
+                KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift),
+                KeySequence::new()
+                    .add_click(KeysClick::new(KeyboardKey::A))
+                    .add_click(KeysClick::new(KeyboardKey::B)),
+            );
+
+        assert_eq!(sequence, expected);
+    }
+
+    #[test]
+    fn test_sequence_display() {
+        let sequence = KeySequence::try_from(
+            "left-ctrl+c,wait 200ms,  enter X3, wait 2000ms, hold left-shift { a b }, \
+             press alt, tab, release alt",
+        )
+        .unwrap();
+
+        assert_eq!(
+            sequence.to_string(),
+            "left-ctrl+c, wait 200ms, enter x3, wait 2s, hold left-shift { a, b }, \
+             press left-alt, tab, release left-alt"
+        );
+        assert_eq!(
+            KeySequence::try_from(sequence.to_string().as_str()).unwrap(),
+            sequence
+        );
+    }
+
+    #[test]
+    fn test_sequence_display_parse_edge_values() {
+        let sequence = KeySequence::new()
+            .add_click(KeysClick::empty())
+            .add_repeat(KeysClick::new(KeyboardKey::A), 0)
+            .add_repeat(KeysClick::new(KeyboardKey::B), 1)
+            .add_wait(Duration::from_micros(1500))
+            .add_wait(Duration::from_nanos(1_000_001))
+            .add_wait(Duration::from_secs(0))
+            .add_press(KeysClick::empty());
+
+        assert_eq!(
+            sequence.to_string(),
+            "none, b x1, wait 1500us, wait 1000001ns, wait 0ms, press none"
+        );
+        assert_eq!(
+            KeySequence::try_from(sequence.to_string().as_str()).unwrap(),
+            sequence
+        );
+        assert_eq!(
+            KeySequence::new().add_repeat(KeysClick::new(KeyboardKey::A), 0),
+            KeySequence::new()
+        );
+    }
+
+    #[test]
+    fn test_sequence_parse_error() {
+        let error = |input: &str| {
+            let error = KeySequence::try_from(input).unwrap_err();
+            (error.reason(), error.token().to_string(), error.span())
+        };
+
+        assert_eq!(KeySequence::try_from(" , "), Ok(KeySequence::new()));
+        assert_eq!(KeySequence::try_from(""), Ok(KeySequence::new()));
+        assert_eq!(
+            KeySequence::try_from(KeySequence::new().to_string().as_str()),
+            Ok(KeySequence::new())
+        );
+        assert_eq!(
+            error("a, ctrl+qq"),
+            (ParseKeyErrorReason::UnknownKey, "qq".into(), 8..10)
+        );
+        assert_eq!(
+            error("wait"),
+            (ParseKeyErrorReason::MissingDuration, "wait".into(), 0..4)
+        );
+        assert_eq!(
+            error("a, wait 10m"),
+            (ParseKeyErrorReason::InvalidDuration, "10m".into(), 8..11)
+        );
+        assert_eq!(
+            error("a x0"),
+            (ParseKeyErrorReason::InvalidRepeatCount, "x0".into(), 2..4)
+        );
+        assert_eq!(
+            error("hold"),
+            (ParseKeyErrorReason::MissingKeys, "hold".into(), 0..4)
+        );
+        assert_eq!(
+            error("a, release"),
+            (ParseKeyErrorReason::MissingKeys, "release".into(), 3..10)
+        );
+        assert_eq!(
+            error("hold a b"),
+            (ParseKeyErrorReason::MissingOpeningBrace, "b".into(), 7..8)
+        );
+        assert_eq!(
+            error("hold a { b"),
+            (ParseKeyErrorReason::MissingClosingBrace, "".into(), 10..10)
+        );
+        assert_eq!(
+            error("a }"),
+            (ParseKeyErrorReason::UnexpectedBrace, "}".into(), 2..3)
+        );
+        assert_eq!(
+            KeySequence::try_from("hold shift { entr }")
+                .unwrap_err()
+                .suggestion(),
+            Some("enter")
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use crate::keyboard::KeysClick;
+use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
+use crate::mouse::{MouseButton, MOUSE_BUTTONS};
+use crate::sequence::KeySequence;
 use serde::{Serialize, Serializer};
 
 impl Serialize for KeysClick {
@@ -7,10 +9,52 @@ impl Serialize for KeysClick {
     }
 }
 
+impl Serialize for KeySequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Serialize for KeyboardKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Serialize for KeyboardModifierKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Serialize for MouseButton {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+// Pressed buttons of a mouse click, written as a list of button names.
+pub(crate) fn serialize_mouse_buttons<S: Serializer>(
+    buttons: &u8,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    // Collected first, so that formats which need it know the length up front.
+    let buttons: Vec<&MouseButton> = MOUSE_BUTTONS
+        .iter()
+        .filter(|button| buttons & button.mask() != 0)
+        .collect();
+
+    serializer.collect_seq(buttons)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::display::{DisplayInfo, DisplayPosition};
     use crate::keyboard::{KeyboardKey, KeyboardModifierKey, KeysClick};
+    use crate::mouse::{MouseButton, MouseClick, MousePosition};
+    use crate::sequence::KeySequence;
     use serde_test::{assert_ser_tokens, Token};
+    use std::time::Duration;
 
     #[test]
     fn test_keys_click_serialize() {
@@ -25,6 +69,136 @@ mod tests {
 
         assert_ser_tokens(&keys_click1, &[Token::String("l")]);
         assert_ser_tokens(&keys_click2, &[Token::String("right-shift+h")]);
-        assert_ser_tokens(&keys_click3, &[Token::String("h+e+l")]);
+        assert_ser_tokens(&keys_click3, &[Token::String("e+h+l")]);
+    }
+
+    #[test]
+    fn test_key_sequence_serialize() {
+        let sequence = KeySequence::new()
+            .add_click(
+                KeysClick::new(KeyboardKey::C).add_modifier(KeyboardModifierKey::LeftControl),
+            )
+            .add_wait(Duration::from_millis(200))
+            .add_repeat(KeysClick::new(KeyboardKey::Enter), 3);
+
+        assert_ser_tokens(
+            &sequence,
+            &[Token::String("left-ctrl+c, wait 200ms, enter x3")],
+        );
+    }
+
+    #[test]
+    fn test_keys_serialize() {
+        assert_ser_tokens(&KeyboardKey::PageUp, &[Token::String("page-up")]);
+        assert_ser_tokens(
+            &KeyboardModifierKey::RightAlt,
+            &[Token::String("right-alt")],
+        );
+    }
+
+    #[test]
+    fn test_mouse_click_serialize() {
+        let click = MouseClick::new()
+            .set_position(10, 20)
+            .set_button(MouseButton::Middle)
+            .set_button(MouseButton::Left)
+            .set_display_index(1);
+
+        assert_ser_tokens(
+            &click,
+            &[
+                Token::Struct {
+                    name: "MouseClick",
+                    len: 5,
+                },
+                Token::Str("buttons"),
+                Token::Seq { len: Some(2) },
+                Token::Str("left"),
+                Token::Str("middle"),
+                Token::SeqEnd,
+                Token::Str("x"),
+                Token::U16(10),
+                Token::Str("y"),
+                Token::U16(20),
+                Token::Str("wheel_position"),
+                Token::U8(0),
+                Token::Str("display_index"),
+                Token::Some,
+                Token::U64(1),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_mouse_position_serialize() {
+        let position = MousePosition {
+            display_index: 0,
+            x: 5,
+            y: 7,
+        };
+
+        assert_ser_tokens(
+            &position,
+            &[
+                Token::Struct {
+                    name: "MousePosition",
+                    len: 3,
+                },
+                Token::Str("display_index"),
+                Token::U64(0),
+                Token::Str("x"),
+                Token::U16(5),
+                Token::Str("y"),
+                Token::U16(7),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_display_info_serialize() {
+        let display_info = DisplayInfo {
+            primary: true,
+            position: DisplayPosition {
+                left: -1920,
+                top: 0,
+                right: 0,
+                bottom: 1080,
+            },
+            width: 1920,
+            height: 1080,
+        };
+
+        assert_ser_tokens(
+            &display_info,
+            &[
+                Token::Struct {
+                    name: "DisplayInfo",
+                    len: 4,
+                },
+                Token::Str("primary"),
+                Token::Bool(true),
+                Token::Str("position"),
+                Token::Struct {
+                    name: "DisplayPosition",
+                    len: 4,
+                },
+                Token::Str("left"),
+                Token::I32(-1920),
+                Token::Str("top"),
+                Token::I32(0),
+                Token::Str("right"),
+                Token::I32(0),
+                Token::Str("bottom"),
+                Token::I32(1080),
+                Token::StructEnd,
+                Token::Str("width"),
+                Token::U16(1920),
+                Token::Str("height"),
+                Token::U16(1080),
+                Token::StructEnd,
+            ],
+        );
     }
 }
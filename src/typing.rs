@@ -0,0 +1,720 @@
+use crate::human::HumanModel;
+use crate::keyboard::{
+    KeyboardError, KeyboardKey, KeyboardModifierKey, KeysClick, Modifiers, KEYBOARD_ROLLOVER,
+};
+use crate::layout::KeyboardLayout;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// Windows-1252 characters placed in the 0x80..0x9F range, reachable with Alt+0128..Alt+0159.
+const WINDOWS_1252_EXTENSIONS: [(char, u8); 27] = [
+    ('€', 0x80),
+    ('‚', 0x82),
+    ('ƒ', 0x83),
+    ('„', 0x84),
+    ('…', 0x85),
+    ('†', 0x86),
+    ('‡', 0x87),
+    ('ˆ', 0x88),
+    ('‰', 0x89),
+    ('Š', 0x8A),
+    ('‹', 0x8B),
+    ('Œ', 0x8C),
+    ('Ž', 0x8E),
+    ('‘', 0x91),
+    ('’', 0x92),
+    ('“', 0x93),
+    ('”', 0x94),
+    ('•', 0x95),
+    ('–', 0x96),
+    ('—', 0x97),
+    ('˜', 0x98),
+    ('™', 0x99),
+    ('š', 0x9A),
+    ('›', 0x9B),
+    ('œ', 0x9C),
+    ('ž', 0x9E),
+    ('Ÿ', 0x9F),
+];
+
+/// What to do with a character the keyboard layout can not produce.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Fallback {
+    /// Enter the character code on the keypad while holding left alt, failing like `Error` for
+    /// characters outside of the Windows-1252 code page.
+    AltNumpad,
+    Skip,
+    Error,
+}
+
+pub struct TypingOptions {
+    fallback: Box<dyn Fn(char) -> Fallback>,
+    delay: Duration,
+    jitter: Duration,
+    seed: u64,
+    human_model: Option<HumanModel>,
+    overlapped: bool,
+}
+
+impl TypingOptions {
+    pub fn new() -> Self {
+        TypingOptions {
+            fallback: Box::new(|_| Fallback::Error),
+            delay: Duration::from_millis(0),
+            jitter: Duration::from_millis(0),
+            seed: rand::random(),
+            human_model: None,
+            overlapped: false,
+        }
+    }
+
+    /// Pause between releasing a key and pressing the next one.
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Randomizes every delay uniformly within the delay plus or minus the jitter.
+    pub fn set_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Makes the jitter and the human model reproducible.
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Presses every key before releasing the previous ones, up to six keys at once, instead of
+    /// releasing every key before the next one. Every report is then followed by the delay, and
+    /// keys go up once down for the hold duration of the keyboard.
+    ///
+    /// Ignored along with a human model.
+    pub fn set_overlapped(mut self, overlapped: bool) -> Self {
+        self.overlapped = overlapped;
+        self
+    }
+
+    /// Types like a human, with its own pauses in place of the delay and jitter.
+    pub fn set_human_model(mut self, human_model: HumanModel) -> Self {
+        self.human_model = Some(human_model);
+        self
+    }
+
+    pub fn set_fallback(self, fallback: Fallback) -> Self {
+        self.set_fallback_with(move |_| fallback)
+    }
+
+    /// Chooses the fallback per character.
+    pub fn set_fallback_with<F: Fn(char) -> Fallback + 'static>(mut self, fallback: F) -> Self {
+        self.fallback = Box::new(fallback);
+        self
+    }
+}
+
+impl TypingOptions {
+    pub(crate) fn next_delay(&self, rng: &mut StdRng) -> Duration {
+        if self.jitter == Duration::from_millis(0) {
+            return self.delay;
+        }
+
+        let delay = self.delay.as_nanos() as u64;
+        let jitter = self.jitter.as_nanos() as u64;
+
+        Duration::from_nanos(rng.gen_range(delay.saturating_sub(jitter)..=delay + jitter))
+    }
+}
+
+impl Default for TypingOptions {
+    fn default() -> Self {
+        TypingOptions::new()
+    }
+}
+
+/// Decimal code typed after Alt for a character of the Windows-1252 code page, with a leading
+/// zero to select the ANSI code page.
+///
+/// Other characters have none, as standard edit controls read bigger codes modulo 256 in the
+/// OEM code page and would type another character.
+pub fn alt_code(character: char) -> Option<String> {
+    let windows_1252 = WINDOWS_1252_EXTENSIONS
+        .iter()
+        .find(|(c, _)| *c == character)
+        .map(|(_, code)| *code as u32);
+
+    match (windows_1252, character as u32) {
+        (Some(code), _) => Some(format!("0{}", code)),
+        (None, code) if code < 0x80 || (0xA0..=0xFF).contains(&code) => Some(format!("0{}", code)),
+        (None, _) => None,
+    }
+}
+
+/// Keystrokes entering a character through Alt+numpad, ending with every key released, if it
+/// has an Alt code.
+///
+/// The keypad only produces digits while num lock is on.
+pub fn alt_numpad_keys(character: char) -> Option<Vec<KeysClick>> {
+    let alt = || KeysClick::empty().add_modifier(KeyboardModifierKey::LeftAlt);
+    let mut keys_clicks = vec![alt()];
+
+    for digit in alt_code(character)?.chars() {
+        let key = match digit {
+            '1' => KeyboardKey::Keypad1,
+            '2' => KeyboardKey::Keypad2,
+            '3' => KeyboardKey::Keypad3,
+            '4' => KeyboardKey::Keypad4,
+            '5' => KeyboardKey::Keypad5,
+            '6' => KeyboardKey::Keypad6,
+            '7' => KeyboardKey::Keypad7,
+            '8' => KeyboardKey::Keypad8,
+            '9' => KeyboardKey::Keypad9,
+            _ => KeyboardKey::Keypad0,
+        };
+
+        keys_clicks.push(alt().add_key(key));
+        keys_clicks.push(alt());
+    }
+
+    keys_clicks.push(KeysClick::empty());
+    Some(keys_clicks)
+}
+
+/// Whether typing the text enters some character through Alt+numpad.
+pub(crate) fn uses_alt_numpad(
+    layout: &KeyboardLayout,
+    text: &str,
+    options: &TypingOptions,
+) -> bool {
+    text.chars().any(|character| {
+        layout.keys_for(character).is_none() && (options.fallback)(character) == Fallback::AltNumpad
+    })
+}
+
+/// Every report needed to type the text, each keystroke followed by a release.
+///
+/// With caps lock on, shift is flipped on the keys caps lock acts upon.
+pub(crate) fn text_keys_clicks(
+    layout: &KeyboardLayout,
+    text: &str,
+    options: &TypingOptions,
+    caps_lock: bool,
+) -> Result<Vec<KeysClick>, KeyboardError> {
+    let mut keys_clicks = Vec::new();
+
+    for character in text.chars() {
+        if let Some(strokes) = layout.keys_for(character) {
+            for stroke in strokes {
+                let stroke = if caps_lock {
+                    compensate_caps_lock(layout, *stroke)
+                } else {
+                    *stroke
+                };
+
+                keys_clicks.push(stroke);
+                keys_clicks.push(KeysClick::empty());
+            }
+
+            continue;
+        }
+
+        match (options.fallback)(character) {
+            Fallback::AltNumpad => match alt_numpad_keys(character) {
+                Some(alt_numpad_keys) => keys_clicks.extend(alt_numpad_keys),
+                None => return Err(KeyboardError::UnmappedCharacter(character)),
+            },
+            Fallback::Skip => {}
+            Fallback::Error => return Err(KeyboardError::UnmappedCharacter(character)),
+        }
+    }
+
+    Ok(keys_clicks)
+}
+
+/// Every report needed to type the text along with the pause following it.
+///
+/// Keystrokes last the hold duration, releases are followed by the delay of the options or by
+/// the pauses of their human model. Overlapped reports are followed by the delay, cut short when
+/// a key has been down for the hold duration.
+pub(crate) fn timed_keys_clicks(
+    layout: &KeyboardLayout,
+    text: &str,
+    options: &TypingOptions,
+    caps_lock: bool,
+    hold_duration: Duration,
+) -> Result<Vec<(KeysClick, Duration)>, KeyboardError> {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+
+    let human_model = match &options.human_model {
+        Some(human_model) => human_model,
+        None if options.overlapped => {
+            let keys_clicks = text_keys_clicks(layout, text, options, caps_lock)?;
+
+            return Ok(overlap_keys_clicks(keys_clicks, hold_duration, || {
+                options.next_delay(&mut rng)
+            }));
+        }
+        None => {
+            let keys_clicks = text_keys_clicks(layout, text, options, caps_lock)?;
+
+            return Ok(keys_clicks
+                .into_iter()
+                .map(|keys_click| match keys_click.key_count() {
+                    0 => (keys_click, options.next_delay(&mut rng)),
+                    _ => (keys_click, hold_duration),
+                })
+                .collect());
+        }
+    };
+
+    let characters = text
+        .chars()
+        .map(|c| text_keys_clicks(layout, c.encode_utf8(&mut [0; 4]), options, caps_lock))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let keys_clicks = human_model.add_typos(characters, &mut rng);
+    let mut timed = Vec::with_capacity(keys_clicks.len());
+    let mut previous = None;
+
+    for (index, (keys_click, pause)) in keys_clicks.iter().enumerate() {
+        if keys_click.key_count() > 0 {
+            previous = keys_click.keys().next();
+            timed.push((*keys_click, hold_duration));
+            continue;
+        }
+
+        let next = keys_clicks[index + 1..]
+            .iter()
+            .find_map(|(keys_click, _)| keys_click.keys().next());
+
+        let pause = match pause {
+            Some(pause) => *pause,
+            None => human_model.pause(previous, next, hold_duration, &mut rng),
+        };
+
+        timed.push((*keys_click, pause));
+    }
+
+    Ok(timed)
+}
+
+/// Keeps the keys of successive keystrokes down, releasing the oldest one when a seventh key is
+/// pressed or once it has been down for the hold duration, every report being followed by the
+/// next delay unless a key goes up within it.
+///
+/// Every key is still pressed in order, and released before being pressed again. Keystrokes
+/// with other modifiers than the held ones first release every key, as do keystrokes not made
+/// of a single key and its release, which are kept as is.
+pub(crate) fn overlap_keys_clicks<F: FnMut() -> Duration>(
+    keys_clicks: Vec<KeysClick>,
+    hold_duration: Duration,
+    mut next_delay: F,
+) -> Vec<(KeysClick, Duration)> {
+    let mut overlap = Overlap {
+        timed: Vec::with_capacity(keys_clicks.len()),
+        held: KeysClick::empty(),
+        pressed: VecDeque::with_capacity(KEYBOARD_ROLLOVER),
+        now: Duration::from_millis(0),
+        hold_duration,
+    };
+    let mut index = 0;
+
+    while index < keys_clicks.len() {
+        let keys_click = keys_clicks[index];
+        let is_stroke =
+            keys_click.key_count() == 1 && keys_clicks.get(index + 1) == Some(&KeysClick::empty());
+
+        let key = match keys_click.keys().next() {
+            Some(key) if is_stroke => key,
+            _ => {
+                overlap.release_all(&mut next_delay);
+                overlap.push(keys_click, next_delay());
+                overlap.held = KeysClick::empty();
+                index += 1;
+                continue;
+            }
+        };
+
+        index += 2;
+
+        if overlap.held.key_count() > 0 && overlap.held.modifiers() != keys_click.modifiers() {
+            overlap.release_all(&mut next_delay);
+        }
+
+        // A key still down has to go up for the next press to produce a character.
+        if overlap.held.has_key(key) {
+            overlap.pressed.retain(|(k, _)| *k != key);
+            overlap.push(overlap.held.remove_key(key), next_delay());
+        }
+
+        let mut held = overlap.held;
+
+        if overlap.pressed.len() == KEYBOARD_ROLLOVER {
+            if let Some((oldest, _)) = overlap.pressed.pop_front() {
+                held = held.remove_key(oldest);
+            }
+        }
+
+        overlap.pressed.push_back((key, overlap.now));
+        overlap.push(
+            held.add_modifiers(keys_click.modifiers()).add_key(key),
+            next_delay(),
+        );
+    }
+
+    overlap.release_all(&mut next_delay);
+    overlap.timed
+}
+
+// Overlapped reports with their pauses, releasing the keys down for the hold duration within
+// the pauses.
+struct Overlap {
+    timed: Vec<(KeysClick, Duration)>,
+    held: KeysClick,
+    // Keys down in the order they were pressed, along with the time they were.
+    pressed: VecDeque<(KeyboardKey, Duration)>,
+    // Time of the last report.
+    now: Duration,
+    hold_duration: Duration,
+}
+
+impl Overlap {
+    fn release_all<F: FnMut() -> Duration>(&mut self, next_delay: &mut F) {
+        if self.held != KeysClick::empty() {
+            self.pressed.clear();
+            self.push(KeysClick::empty(), next_delay());
+        }
+    }
+
+    fn push(&mut self, report: KeysClick, pause: Duration) {
+        let end = self.now + pause;
+
+        self.held = report;
+        self.timed.push((report, pause));
+
+        while let Some(&(_, pressed_at)) = self.pressed.front() {
+            let deadline = pressed_at + self.hold_duration;
+
+            if deadline > end {
+                break;
+            }
+
+            // Keys pressed together go up together.
+            while let Some(&(key, _)) = self
+                .pressed
+                .front()
+                .filter(|(_, at)| *at + self.hold_duration <= deadline)
+            {
+                self.held = self.held.remove_key(key);
+                self.pressed.pop_front();
+            }
+
+            if self.held.key_count() == 0 {
+                self.held = KeysClick::empty();
+            }
+
+            // The pause of the last report is cut short, the rest follows the release.
+            if let Some(last) = self.timed.last_mut() {
+                last.1 = deadline - self.now;
+            }
+
+            self.now = deadline;
+            self.timed.push((self.held, end - deadline));
+        }
+
+        self.now = end;
+    }
+}
+
+fn compensate_caps_lock(layout: &KeyboardLayout, stroke: KeysClick) -> KeysClick {
+    let shift = Modifiers::LEFT_SHIFT | Modifiers::RIGHT_SHIFT;
+
+    // Caps lock only stands for shift in the plain and shifted states.
+    if !(stroke.modifiers() - shift).is_empty()
+        || !stroke.keys().any(|k| layout.is_caps_lock_key(k))
+    {
+        return stroke;
+    }
+
+    if stroke.modifiers().intersects(shift) {
+        stroke
+            .remove_modifier(KeyboardModifierKey::LeftShift)
+            .remove_modifier(KeyboardModifierKey::RightShift)
+    } else {
+        stroke.add_modifier(KeyboardModifierKey::LeftShift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{KeyboardError, KeyboardKey, KeyboardModifierKey, KeysClick};
+    use crate::layout::KeyboardLayout;
+    use crate::typing::{
+        alt_code, alt_numpad_keys, overlap_keys_clicks, text_keys_clicks, Fallback, TypingOptions,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    #[test]
+    fn test_alt_code() {
+        assert_eq!(alt_code('A').unwrap(), "065");
+        assert_eq!(alt_code('©').unwrap(), "0169");
+        assert_eq!(alt_code('°').unwrap(), "0176");
+        assert_eq!(alt_code('—').unwrap(), "0151");
+        assert_eq!(alt_code('€').unwrap(), "0128");
+        assert_eq!(alt_code('→'), None);
+        assert_eq!(alt_code('\u{81}'), None);
+    }
+
+    #[test]
+    fn test_alt_numpad_keys() {
+        let alt = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftAlt);
+
+        assert_eq!(alt_numpad_keys('→'), None);
+        assert_eq!(
+            alt_numpad_keys('©').unwrap(),
+            vec![
+                alt,
+                alt.add_key(KeyboardKey::Keypad0),
+                alt,
+                alt.add_key(KeyboardKey::Keypad1),
+                alt,
+                alt.add_key(KeyboardKey::Keypad6),
+                alt,
+                alt.add_key(KeyboardKey::Keypad9),
+                alt,
+                KeysClick::empty(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_keys_clicks() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new();
+
+        assert_eq!(
+            text_keys_clicks(&layout, "Hi", &options, false).unwrap(),
+            vec![
+                KeysClick::new(KeyboardKey::H).add_modifier(KeyboardModifierKey::LeftShift),
+                KeysClick::empty(),
+                KeysClick::new(KeyboardKey::I),
+                KeysClick::empty(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_keys_clicks_fallback() {
+        let layout = KeyboardLayout::us();
+
+        let error = TypingOptions::new();
+        let skip = TypingOptions::new().set_fallback(Fallback::Skip);
+        let alt_numpad = TypingOptions::new().set_fallback(Fallback::AltNumpad);
+        let per_character = TypingOptions::new().set_fallback_with(|c| match c {
+            '©' => Fallback::AltNumpad,
+            _ => Fallback::Skip,
+        });
+
+        let mut expected = vec![KeysClick::new(KeyboardKey::A), KeysClick::empty()];
+        expected.extend(alt_numpad_keys('©').unwrap());
+
+        assert_eq!(
+            text_keys_clicks(&layout, "a©", &error, false).unwrap_err(),
+            KeyboardError::UnmappedCharacter('©')
+        );
+        assert_eq!(
+            text_keys_clicks(&layout, "a©", &skip, false).unwrap(),
+            vec![KeysClick::new(KeyboardKey::A), KeysClick::empty()]
+        );
+        assert_eq!(
+            text_keys_clicks(&layout, "a©", &alt_numpad, false).unwrap(),
+            expected
+        );
+        assert_eq!(
+            text_keys_clicks(&layout, "a©°", &per_character, false).unwrap(),
+            expected
+        );
+        assert_eq!(
+            text_keys_clicks(&layout, "a→", &alt_numpad, false).unwrap_err(),
+            KeyboardError::UnmappedCharacter('→')
+        );
+    }
+
+    #[test]
+    fn test_text_keys_clicks_caps_lock() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new();
+
+        assert_eq!(
+            text_keys_clicks(&layout, "Hi1!", &options, true).unwrap(),
+            vec![
+                KeysClick::new(KeyboardKey::H),
+                KeysClick::empty(),
+                KeysClick::new(KeyboardKey::I).add_modifier(KeyboardModifierKey::LeftShift),
+                KeysClick::empty(),
+                KeysClick::new(KeyboardKey::Num1),
+                KeysClick::empty(),
+                KeysClick::new(KeyboardKey::Num1).add_modifier(KeyboardModifierKey::LeftShift),
+                KeysClick::empty(),
+            ]
+        );
+    }
+
+    // Overlapped reports of keys which never stay down long enough to be released on time.
+    fn untimed(keys_clicks: Vec<KeysClick>) -> Vec<KeysClick> {
+        overlap_keys_clicks(keys_clicks, Duration::from_secs(1), || {
+            Duration::from_millis(0)
+        })
+        .into_iter()
+        .map(|(keys_click, _)| keys_click)
+        .collect()
+    }
+
+    #[test]
+    fn test_overlap_keys_clicks() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new();
+        let overlap = |text| untimed(text_keys_clicks(&layout, text, &options, false).unwrap());
+
+        let a = KeysClick::new(KeyboardKey::A);
+        let shift = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftShift);
+
+        assert_eq!(
+            overlap("abc"),
+            vec![
+                a,
+                a.add_key(KeyboardKey::B),
+                a.add_key(KeyboardKey::B).add_key(KeyboardKey::C),
+                KeysClick::empty(),
+            ]
+        );
+        assert_eq!(
+            overlap("aAb"),
+            vec![
+                a,
+                KeysClick::empty(),
+                shift.add_key(KeyboardKey::A),
+                KeysClick::empty(),
+                KeysClick::new(KeyboardKey::B),
+                KeysClick::empty(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlap_keys_clicks_releases_repeated_keys() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new();
+        let keys_clicks = text_keys_clicks(&layout, "abba", &options, false).unwrap();
+
+        let a = KeysClick::new(KeyboardKey::A);
+        let ab = a.add_key(KeyboardKey::B);
+
+        assert_eq!(
+            untimed(keys_clicks),
+            vec![
+                a,
+                ab,
+                a,
+                ab,
+                KeysClick::new(KeyboardKey::B),
+                ab,
+                KeysClick::empty()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlap_keys_clicks_releases_after_hold_duration() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new();
+        let keys_clicks = text_keys_clicks(&layout, "abc", &options, false).unwrap();
+        let ms = Duration::from_millis;
+
+        let a = KeysClick::new(KeyboardKey::A);
+        let b = KeysClick::new(KeyboardKey::B);
+
+        assert_eq!(
+            overlap_keys_clicks(keys_clicks, ms(15), || ms(10)),
+            vec![
+                (a, ms(10)),
+                (a.add_key(KeyboardKey::B), ms(5)),
+                (b, ms(5)),
+                (b.add_key(KeyboardKey::C), ms(5)),
+                (KeysClick::new(KeyboardKey::C), ms(5)),
+                (KeysClick::empty(), ms(10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlap_keys_clicks_rollover() {
+        let layout = KeyboardLayout::us();
+        let options = TypingOptions::new().set_fallback(Fallback::AltNumpad);
+        let keys_clicks = text_keys_clicks(&layout, "abcdefgh©", &options, false).unwrap();
+        let overlapped = untimed(keys_clicks);
+
+        let keys = |keys_click: &KeysClick| keys_click.keys().collect::<Vec<_>>();
+
+        assert_eq!(
+            keys(&overlapped[5]),
+            vec![
+                KeyboardKey::A,
+                KeyboardKey::B,
+                KeyboardKey::C,
+                KeyboardKey::D,
+                KeyboardKey::E,
+                KeyboardKey::F,
+            ]
+        );
+        assert_eq!(
+            keys(&overlapped[7]),
+            vec![
+                KeyboardKey::C,
+                KeyboardKey::D,
+                KeyboardKey::E,
+                KeyboardKey::F,
+                KeyboardKey::G,
+                KeyboardKey::H,
+            ]
+        );
+        assert_eq!(overlapped[8], KeysClick::empty());
+        assert_eq!(overlapped[9..], alt_numpad_keys('©').unwrap()[..]);
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let fixed = TypingOptions::new().set_delay(Duration::from_millis(50));
+        let jittered = TypingOptions::new()
+            .set_delay(Duration::from_millis(50))
+            .set_jitter(Duration::from_millis(20));
+
+        assert_eq!(fixed.next_delay(&mut rng), Duration::from_millis(50));
+
+        for _ in 0..100 {
+            let delay = jittered.next_delay(&mut rng);
+
+            assert!(delay >= Duration::from_millis(30));
+            assert!(delay <= Duration::from_millis(70));
+        }
+    }
+
+    #[test]
+    fn test_next_delay_saturates() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let options = TypingOptions::new()
+            .set_delay(Duration::from_millis(5))
+            .set_jitter(Duration::from_millis(20));
+
+        for _ in 0..100 {
+            assert!(options.next_delay(&mut rng) <= Duration::from_millis(25));
+        }
+    }
+}
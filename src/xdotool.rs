@@ -0,0 +1,932 @@
+use crate::keyboard::{
+    closest_name, Keyboard, KeyboardError, KeyboardKey, KeyboardModifierKey, KeysClick,
+    ParseKeyError, ParseKeyErrorReason,
+};
+use crate::layout::KeyboardLayout;
+use crate::mouse::{Mouse, MouseButton, MouseClick};
+use crate::sequence::KeySequence;
+use crate::typing::TypingOptions;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::time::Duration;
+
+// Default pauses of xdotool between keystrokes and between repeated clicks.
+const KEY_DELAY: Duration = Duration::from_millis(12);
+const CLICK_DELAY: Duration = Duration::from_millis(100);
+
+const fn key(key: KeyboardKey) -> KeysClick {
+    KeysClick::new(key)
+}
+
+const fn shifted(key: KeyboardKey) -> KeysClick {
+    KeysClick::new(key).add_modifier(KeyboardModifierKey::LeftShift)
+}
+
+const fn modifier(modifier: KeyboardModifierKey) -> KeysClick {
+    KeysClick::empty().add_modifier(modifier)
+}
+
+// X keysym names, compared ignoring case, punctuation being placed as on a US layout. Other
+// names are looked up among the key names of this crate.
+const KEYSYMS: &[(&str, KeysClick)] = &[
+    ("Return", key(KeyboardKey::Enter)),
+    ("KP_Enter", key(KeyboardKey::KeypadEnter)),
+    ("Escape", key(KeyboardKey::Esc)),
+    ("BackSpace", key(KeyboardKey::Del)),
+    ("Tab", key(KeyboardKey::Tab)),
+    ("space", key(KeyboardKey::Space)),
+    ("Delete", key(KeyboardKey::DeleteForward)),
+    ("Insert", key(KeyboardKey::Insert)),
+    ("Home", key(KeyboardKey::Home)),
+    ("End", key(KeyboardKey::End)),
+    ("Prior", key(KeyboardKey::PageUp)),
+    ("Page_Up", key(KeyboardKey::PageUp)),
+    ("Next", key(KeyboardKey::PageDown)),
+    ("Page_Down", key(KeyboardKey::PageDown)),
+    ("Left", key(KeyboardKey::LeftArrow)),
+    ("Up", key(KeyboardKey::UpArrow)),
+    ("Right", key(KeyboardKey::RightArrow)),
+    ("Down", key(KeyboardKey::DownArrow)),
+    ("Print", key(KeyboardKey::PrintScreen)),
+    ("Scroll_Lock", key(KeyboardKey::ScrollLock)),
+    ("Pause", key(KeyboardKey::Pause)),
+    ("Caps_Lock", key(KeyboardKey::CapsLock)),
+    ("Num_Lock", key(KeyboardKey::NumLock)),
+    ("Menu", key(KeyboardKey::Application)),
+    ("KP_0", key(KeyboardKey::Keypad0)),
+    ("KP_1", key(KeyboardKey::Keypad1)),
+    ("KP_2", key(KeyboardKey::Keypad2)),
+    ("KP_3", key(KeyboardKey::Keypad3)),
+    ("KP_4", key(KeyboardKey::Keypad4)),
+    ("KP_5", key(KeyboardKey::Keypad5)),
+    ("KP_6", key(KeyboardKey::Keypad6)),
+    ("KP_7", key(KeyboardKey::Keypad7)),
+    ("KP_8", key(KeyboardKey::Keypad8)),
+    ("KP_9", key(KeyboardKey::Keypad9)),
+    ("KP_Add", key(KeyboardKey::KeypadPlus)),
+    ("KP_Subtract", key(KeyboardKey::KeypadMinus)),
+    ("KP_Multiply", key(KeyboardKey::KeypadAsterisk)),
+    ("KP_Divide", key(KeyboardKey::KeypadSlash)),
+    ("KP_Decimal", key(KeyboardKey::KeypadPeriod)),
+    ("XF86AudioMute", key(KeyboardKey::Mute)),
+    ("XF86AudioRaiseVolume", key(KeyboardKey::VolumeUp)),
+    ("XF86AudioLowerVolume", key(KeyboardKey::VolumeDown)),
+    ("minus", key(KeyboardKey::Minus)),
+    ("equal", key(KeyboardKey::Equal)),
+    ("bracketleft", key(KeyboardKey::LeftBracket)),
+    ("bracketright", key(KeyboardKey::RightBracket)),
+    ("backslash", key(KeyboardKey::Backslash)),
+    ("semicolon", key(KeyboardKey::Semicolon)),
+    ("apostrophe", key(KeyboardKey::Quote)),
+    ("grave", key(KeyboardKey::Grave)),
+    ("comma", key(KeyboardKey::Comma)),
+    ("period", key(KeyboardKey::Period)),
+    ("slash", key(KeyboardKey::Slash)),
+    ("exclam", shifted(KeyboardKey::Num1)),
+    ("at", shifted(KeyboardKey::Num2)),
+    ("numbersign", shifted(KeyboardKey::Num3)),
+    ("dollar", shifted(KeyboardKey::Num4)),
+    ("percent", shifted(KeyboardKey::Num5)),
+    ("asciicircum", shifted(KeyboardKey::Num6)),
+    ("ampersand", shifted(KeyboardKey::Num7)),
+    ("asterisk", shifted(KeyboardKey::Num8)),
+    ("parenleft", shifted(KeyboardKey::Num9)),
+    ("parenright", shifted(KeyboardKey::Num0)),
+    ("underscore", shifted(KeyboardKey::Minus)),
+    ("plus", shifted(KeyboardKey::Equal)),
+    ("braceleft", shifted(KeyboardKey::LeftBracket)),
+    ("braceright", shifted(KeyboardKey::RightBracket)),
+    ("bar", shifted(KeyboardKey::Backslash)),
+    ("colon", shifted(KeyboardKey::Semicolon)),
+    ("quotedbl", shifted(KeyboardKey::Quote)),
+    ("asciitilde", shifted(KeyboardKey::Grave)),
+    ("less", shifted(KeyboardKey::Comma)),
+    ("greater", shifted(KeyboardKey::Period)),
+    ("question", shifted(KeyboardKey::Slash)),
+    ("ctrl", modifier(KeyboardModifierKey::LeftControl)),
+    ("Control_L", modifier(KeyboardModifierKey::LeftControl)),
+    ("Control_R", modifier(KeyboardModifierKey::RightControl)),
+    ("shift", modifier(KeyboardModifierKey::LeftShift)),
+    ("Shift_L", modifier(KeyboardModifierKey::LeftShift)),
+    ("Shift_R", modifier(KeyboardModifierKey::RightShift)),
+    ("alt", modifier(KeyboardModifierKey::LeftAlt)),
+    ("Alt_L", modifier(KeyboardModifierKey::LeftAlt)),
+    ("Alt_R", modifier(KeyboardModifierKey::RightAlt)),
+    ("ISO_Level3_Shift", modifier(KeyboardModifierKey::RightAlt)),
+    ("super", modifier(KeyboardModifierKey::LeftWindows)),
+    ("Super_L", modifier(KeyboardModifierKey::LeftWindows)),
+    ("Super_R", modifier(KeyboardModifierKey::RightWindows)),
+];
+
+const COMMANDS: [&str; 9] = [
+    "key",
+    "keydown",
+    "keyup",
+    "type",
+    "mousemove",
+    "click",
+    "mousedown",
+    "mouseup",
+    "sleep",
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum XdotoolCommand {
+    /// Taps the keys clicks one after another, all of them as many times as the repeat count,
+    /// written as "key --delay 50 --repeat 2 ctrl+shift+t Return".
+    Key {
+        keys_clicks: Vec<KeysClick>,
+        repeat: u32,
+        delay: Duration,
+    },
+    /// Presses the keys clicks, written as "keydown shift".
+    KeyDown(Vec<KeysClick>),
+    /// Releases the keys clicks, written as "keyup shift".
+    KeyUp(Vec<KeysClick>),
+    /// Types the text on the layout given when running, written as "type --delay 50 'text'".
+    /// Every argument up to the end of the line is text.
+    Type { text: String, delay: Duration },
+    /// Moves the pointer to a position of the primary display, written as "mousemove 100 200".
+    MouseMove(u16, u16),
+    /// Clicks the button where the pointer is, written as "click --repeat 2 1".
+    Click {
+        button: MouseButton,
+        repeat: u32,
+        delay: Duration,
+    },
+    /// Presses the button, written as "mousedown 1".
+    MouseDown(MouseButton),
+    /// Releases the button, written as "mouseup 1".
+    MouseUp(MouseButton),
+    /// Waits before the next command, written as "sleep 0.5" in seconds.
+    Sleep(Duration),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum XdotoolError {
+    Keyboard(KeyboardError),
+    MouseSendFailed,
+}
+
+impl Display for XdotoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            XdotoolError::Keyboard(error) => write!(f, "{}", error),
+            XdotoolError::MouseSendFailed => write!(f, "failed to send the mouse report"),
+        }
+    }
+}
+
+impl From<KeyboardError> for XdotoolError {
+    fn from(error: KeyboardError) -> Self {
+        XdotoolError::Keyboard(error)
+    }
+}
+
+/// A subset of xdotool commands, one or several per line as in "xdotool key ctrl+l type url".
+///
+/// The "xdotool" word, comments starting with "#" and quotes, as written in a shell script,
+/// are understood. Keys are X keysyms such as "Return" or "ctrl", mouse buttons are 1 for
+/// left, 2 for middle and 3 for right.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct XdotoolScript {
+    commands: Vec<XdotoolCommand>,
+}
+
+impl XdotoolScript {
+    pub fn new() -> Self {
+        XdotoolScript::default()
+    }
+
+    pub fn add_command(mut self, command: XdotoolCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn commands(&self) -> &[XdotoolCommand] {
+        &self.commands
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseKeyError> {
+        let tokens = tokenize(input)?;
+        let mut script = XdotoolScript::new();
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let command = &tokens[index];
+            index += 1;
+
+            if command.is_word("xdotool") {
+                continue;
+            }
+
+            if !COMMANDS.iter().any(|name| command.is_word(name)) {
+                let names = COMMANDS.iter().cloned();
+                let error = command.error(ParseKeyErrorReason::UnsupportedCommand);
+
+                return Err(error.with_suggestion(closest_name(&command.text, names)));
+            }
+
+            // Arguments run up to the end of the line or the next command, except for the text
+            // of "type" which takes the rest of the line as xdotool does.
+            let length = tokens[index..]
+                .iter()
+                .take_while(|token| {
+                    token.line == command.line
+                        && (command.is_word("type")
+                            || !token.is_word("xdotool")
+                                && !COMMANDS.iter().any(|name| token.is_word(name)))
+                })
+                .count();
+
+            let arguments = &tokens[index..index + length];
+            index += length;
+
+            script = script.add_command(parse_command(command, arguments)?);
+        }
+
+        Ok(script)
+    }
+
+    /// Runs the commands, typing text on the layout and pausing with the clock of the keyboard.
+    ///
+    /// Clicks before any "mousemove" happen where the cursor currently is, or at the top left
+    /// corner of the primary display where the cursor can not be read.
+    pub fn run(
+        &self,
+        keyboard: &Keyboard,
+        mouse: &Mouse,
+        layout: &KeyboardLayout,
+    ) -> Result<(), XdotoolError> {
+        let mut position = None;
+        let mut buttons = Vec::new();
+
+        for command in &self.commands {
+            match command {
+                XdotoolCommand::Key {
+                    keys_clicks,
+                    repeat,
+                    delay,
+                } => {
+                    let mut sequence = KeySequence::new();
+                    let strokes = keys_clicks
+                        .iter()
+                        .cycle()
+                        .take(keys_clicks.len() * *repeat as usize);
+
+                    for (index, keys_click) in strokes.enumerate() {
+                        if index > 0 {
+                            sequence = sequence.add_wait(*delay);
+                        }
+
+                        sequence = sequence.add_click(*keys_click);
+                    }
+
+                    keyboard.send_sequence(&sequence)?;
+                }
+                XdotoolCommand::KeyDown(keys_clicks) => {
+                    let sequence = keys_clicks
+                        .iter()
+                        .fold(KeySequence::new(), |sequence, k| sequence.add_press(*k));
+
+                    keyboard.send_sequence(&sequence)?;
+                }
+                XdotoolCommand::KeyUp(keys_clicks) => {
+                    let sequence = keys_clicks
+                        .iter()
+                        .fold(KeySequence::new(), |sequence, k| sequence.add_release(*k));
+
+                    keyboard.send_sequence(&sequence)?;
+                }
+                XdotoolCommand::Type { text, delay } => {
+                    let options = TypingOptions::new().set_delay(*delay);
+
+                    keyboard.type_text(layout, text, &options)?;
+                }
+                XdotoolCommand::MouseMove(x, y) => {
+                    let moved = Position {
+                        display_index: None,
+                        x: *x,
+                        y: *y,
+                    };
+
+                    position = Some(moved);
+                    send_mouse(mouse, moved, &buttons)?;
+                }
+                XdotoolCommand::Click {
+                    button,
+                    repeat,
+                    delay,
+                } => {
+                    let at = *position.get_or_insert_with(|| current_position(mouse));
+                    let pressed: Vec<MouseButton> =
+                        buttons.iter().chain([*button].iter()).cloned().collect();
+
+                    for count in 0..*repeat {
+                        if count > 0 {
+                            sleep(keyboard, *delay)?;
+                        }
+
+                        send_mouse(mouse, at, &pressed)?;
+                        send_mouse(mouse, at, &buttons)?;
+                    }
+                }
+                XdotoolCommand::MouseDown(button) => {
+                    let at = *position.get_or_insert_with(|| current_position(mouse));
+
+                    if !buttons.contains(button) {
+                        buttons.push(*button);
+                    }
+
+                    send_mouse(mouse, at, &buttons)?;
+                }
+                XdotoolCommand::MouseUp(button) => {
+                    let at = *position.get_or_insert_with(|| current_position(mouse));
+
+                    buttons.retain(|b| b != button);
+                    send_mouse(mouse, at, &buttons)?;
+                }
+                XdotoolCommand::Sleep(duration) => sleep(keyboard, *duration)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for XdotoolScript {
+    type Error = ParseKeyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        XdotoolScript::parse(value)
+    }
+}
+
+// Pointer position, on the primary display unless told otherwise.
+#[derive(Debug, Copy, Clone)]
+struct Position {
+    display_index: Option<usize>,
+    x: u16,
+    y: u16,
+}
+
+#[cfg(windows)]
+fn current_position(mouse: &Mouse) -> Position {
+    let position = mouse.get_mouse_position();
+
+    Position {
+        display_index: Some(position.display_index),
+        x: position.x,
+        y: position.y,
+    }
+}
+
+#[cfg(not(windows))]
+fn current_position(_mouse: &Mouse) -> Position {
+    Position {
+        display_index: None,
+        x: 0,
+        y: 0,
+    }
+}
+
+fn send_mouse(
+    mouse: &Mouse,
+    position: Position,
+    buttons: &[MouseButton],
+) -> Result<(), XdotoolError> {
+    let mut click = MouseClick::new().set_position(position.x, position.y);
+
+    if let Some(display_index) = position.display_index {
+        click = click.set_display_index(display_index);
+    }
+
+    let click = buttons
+        .iter()
+        .fold(click, |click, button| click.set_button(*button));
+
+    if mouse.send_click(click) {
+        Ok(())
+    } else {
+        Err(XdotoolError::MouseSendFailed)
+    }
+}
+
+fn sleep(keyboard: &Keyboard, duration: Duration) -> Result<(), XdotoolError> {
+    keyboard.send_sequence(&KeySequence::new().add_wait(duration))?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    raw: String,
+    start: usize,
+    line: usize,
+    quoted: bool,
+}
+
+impl Token {
+    fn is_word(&self, word: &str) -> bool {
+        !self.quoted && self.text == word
+    }
+
+    // Points at the token as written, with its quotes and escapes.
+    fn error(&self, reason: ParseKeyErrorReason) -> ParseKeyError {
+        ParseKeyError::new(&self.raw, self.start, reason)
+    }
+}
+
+// Splits the input into words as a shell does, with single and double quotes and backslash
+// escapes, leaving out comments.
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseKeyError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let mut line = 0;
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c == '\n' {
+            line += 1;
+            chars.next();
+            continue;
+        }
+
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '#' {
+            while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            continue;
+        }
+
+        let token_line = line;
+        let mut text = String::new();
+        let mut quote = None;
+        let mut quoted = false;
+        let mut end = start;
+
+        while let Some(&(index, c)) = chars.peek() {
+            match (quote, c) {
+                (None, c) if c.is_whitespace() => break,
+                (None, '\'') | (None, '"') => {
+                    quote = Some((c, index));
+                    quoted = true;
+                }
+                (Some((q, _)), c) if c == q => quote = None,
+                (Some(('\'', _)), c) => text.push(c),
+                (_, '\\') => {
+                    chars.next();
+
+                    // An escaped line break continues the line, as in a shell.
+                    match chars.next() {
+                        Some((index, '\n')) => end = index + 1,
+                        Some((index, c)) => {
+                            text.push(c);
+                            quoted = true;
+                            end = index + c.len_utf8();
+                        }
+                        None => {}
+                    }
+
+                    continue;
+                }
+                (_, c) => text.push(c),
+            }
+
+            // Quoted text may span lines.
+            if c == '\n' {
+                line += 1;
+            }
+
+            chars.next();
+            end = index + c.len_utf8();
+        }
+
+        if let Some((_, index)) = quote {
+            let reason = ParseKeyErrorReason::MissingClosingQuote;
+            return Err(ParseKeyError::new(&input[index..], index, reason));
+        }
+
+        if text.is_empty() && !quoted {
+            continue;
+        }
+
+        tokens.push(Token {
+            text,
+            raw: input[start..end].to_string(),
+            start,
+            line: token_line,
+            quoted,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_command(command: &Token, arguments: &[Token]) -> Result<XdotoolCommand, ParseKeyError> {
+    let mut repeat = 1;
+    let mut delay = None;
+    let mut index = 0;
+
+    // Options come first, as in "key --delay 50 Return".
+    while let Some(option) = arguments.get(index).filter(|a| a.text.starts_with("--")) {
+        let value = arguments.get(index + 1);
+        index += 2;
+
+        match option.text.as_str() {
+            "--repeat" if command.is_word("key") || command.is_word("click") => {
+                repeat = parse_number(option, value)?;
+            }
+            "--delay" if command.is_word("key") || command.is_word("click") => {
+                delay = Some(Duration::from_millis(parse_number(option, value)?));
+            }
+            "--delay" if command.is_word("type") => {
+                delay = Some(Duration::from_millis(parse_number(option, value)?));
+            }
+            _ => return Err(option.error(ParseKeyErrorReason::UnsupportedCommand)),
+        }
+    }
+
+    let arguments = &arguments[index..];
+
+    match command.text.as_str() {
+        "key" => Ok(XdotoolCommand::Key {
+            keys_clicks: parse_keysyms(command, arguments)?,
+            repeat,
+            delay: delay.unwrap_or(KEY_DELAY),
+        }),
+        "keydown" => Ok(XdotoolCommand::KeyDown(parse_keysyms(command, arguments)?)),
+        "keyup" => Ok(XdotoolCommand::KeyUp(parse_keysyms(command, arguments)?)),
+        "type" => Ok(XdotoolCommand::Type {
+            text: arguments.iter().map(|a| a.text.as_str()).collect(),
+            delay: delay.unwrap_or(KEY_DELAY),
+        }),
+        "mousemove" => match arguments {
+            [x, y] => Ok(XdotoolCommand::MouseMove(
+                parse_number(command, Some(x))?,
+                parse_number(command, Some(y))?,
+            )),
+            [_, _, extra, ..] => Err(extra.error(ParseKeyErrorReason::InvalidArgument)),
+            _ => Err(command.error(ParseKeyErrorReason::InvalidArgument)),
+        },
+        "click" => Ok(XdotoolCommand::Click {
+            button: parse_button(command, arguments)?,
+            repeat,
+            delay: delay.unwrap_or(CLICK_DELAY),
+        }),
+        "mousedown" => Ok(XdotoolCommand::MouseDown(parse_button(command, arguments)?)),
+        "mouseup" => Ok(XdotoolCommand::MouseUp(parse_button(command, arguments)?)),
+        _ => match arguments {
+            [seconds] => seconds
+                .text
+                .parse::<f64>()
+                .ok()
+                // Durations past u64::MAX seconds do not fit.
+                .filter(|seconds| *seconds >= 0.0 && *seconds < u64::MAX as f64)
+                .map(|seconds| XdotoolCommand::Sleep(Duration::from_secs_f64(seconds)))
+                .ok_or_else(|| seconds.error(ParseKeyErrorReason::InvalidDuration)),
+            [] => Err(command.error(ParseKeyErrorReason::MissingDuration)),
+            [_, extra, ..] => Err(extra.error(ParseKeyErrorReason::InvalidArgument)),
+        },
+    }
+}
+
+// Parses the value of an option, or the option itself when it has none.
+fn parse_number<T: std::str::FromStr>(
+    option: &Token,
+    value: Option<&Token>,
+) -> Result<T, ParseKeyError> {
+    match value {
+        Some(value) => value
+            .text
+            .parse()
+            .map_err(|_| value.error(ParseKeyErrorReason::InvalidArgument)),
+        None => Err(option.error(ParseKeyErrorReason::InvalidArgument)),
+    }
+}
+
+fn parse_button(command: &Token, arguments: &[Token]) -> Result<MouseButton, ParseKeyError> {
+    match arguments {
+        [button] => match button.text.as_str() {
+            "1" => Ok(MouseButton::Left),
+            "2" => Ok(MouseButton::Middle),
+            "3" => Ok(MouseButton::Right),
+            // Wheel and extra buttons are missing from the vmulti mouse.
+            "4" | "5" | "6" | "7" | "8" | "9" => {
+                Err(button.error(ParseKeyErrorReason::UnsupportedCommand))
+            }
+            _ => Err(button.error(ParseKeyErrorReason::InvalidArgument)),
+        },
+        [] => Err(command.error(ParseKeyErrorReason::InvalidArgument)),
+        [_, extra, ..] => Err(extra.error(ParseKeyErrorReason::InvalidArgument)),
+    }
+}
+
+fn parse_keysyms(command: &Token, arguments: &[Token]) -> Result<Vec<KeysClick>, ParseKeyError> {
+    if arguments.is_empty() {
+        return Err(command.error(ParseKeyErrorReason::MissingKeys));
+    }
+
+    arguments.iter().map(parse_chord).collect()
+}
+
+// Parses keysyms joined with plus signs, as in "ctrl+shift+t".
+fn parse_chord(token: &Token) -> Result<KeysClick, ParseKeyError> {
+    let mut keys_click = KeysClick::empty();
+    let mut offset = 0;
+
+    for name in token.text.split('+') {
+        let start = token.start + offset;
+        offset += name.len() + 1;
+
+        let found = keysym(name).ok_or_else(|| {
+            let names = KEYSYMS.iter().map(|(name, _)| *name);
+            let reason = match name {
+                "" => ParseKeyErrorReason::EmptySegment,
+                _ => ParseKeyErrorReason::UnknownKey,
+            };
+
+            // Quoted keysyms are pointed at as a whole.
+            let error = match token.quoted {
+                true => token.error(reason),
+                false => ParseKeyError::new(name, start, reason),
+            };
+
+            error.with_suggestion(closest_name(name, names))
+        })?;
+
+        keys_click = found.keys().fold(keys_click, KeysClick::add_key);
+        keys_click = keys_click.add_modifiers(found.modifiers());
+    }
+
+    Ok(keys_click)
+}
+
+fn keysym(name: &str) -> Option<KeysClick> {
+    // Capital letters are their shifted key, as "A" in "key A".
+    if name.len() == 1 && name.chars().all(|c| c.is_ascii_uppercase()) {
+        let key = KeyboardKey::try_from(name).ok()?;
+        return Some(KeysClick::new(key).add_modifier(KeyboardModifierKey::LeftShift));
+    }
+
+    KEYSYMS
+        .iter()
+        .find(|(keysym, _)| keysym.eq_ignore_ascii_case(name))
+        .map(|(_, keys_click)| *keys_click)
+        .or_else(|| KeysClick::try_from(name).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::testing::RecordingClock;
+    use crate::device::testing::RecordingTransport;
+    use crate::keyboard::{
+        Keyboard, KeyboardError, KeyboardKey, KeyboardModifierKey, KeysClick, ParseKeyErrorReason,
+    };
+    use crate::layout::KeyboardLayout;
+    use crate::lock::testing::FakeLockState;
+    use crate::mouse::testing::mouse;
+    use crate::mouse::MouseButton;
+    use crate::xdotool::{XdotoolCommand, XdotoolError, XdotoolScript};
+    use std::ops::Range;
+    use std::time::Duration;
+
+    fn error(input: &str) -> (ParseKeyErrorReason, String, Range<usize>) {
+        let error = XdotoolScript::parse(input).unwrap_err();
+        (error.reason(), error.token().into(), error.span())
+    }
+
+    #[test]
+    fn test_xdotool_parse() {
+        let script = XdotoolScript::parse(
+            "xdotool key ctrl+shift+t Return  # new tab\n\
+             xdotool type 'hello world' \"!\"\n\
+             sleep 0.5\n\
+             mousemove 100 200 click --repeat 2 1\n\
+             keydown super key --delay 50 A keyup Super_L mousedown 3 mouseup 3",
+        )
+        .unwrap();
+
+        let ctrl_shift_t = KeysClick::new(KeyboardKey::T)
+            .add_modifier(KeyboardModifierKey::LeftControl)
+            .add_modifier(KeyboardModifierKey::LeftShift);
+        let shift_a = KeysClick::new(KeyboardKey::A).add_modifier(KeyboardModifierKey::LeftShift);
+        let super_key = KeysClick::empty().add_modifier(KeyboardModifierKey::LeftWindows);
+
+        let expected = XdotoolScript::new()
+            .add_command(XdotoolCommand::Key {
+                keys_clicks: vec![ctrl_shift_t, KeysClick::new(KeyboardKey::Enter)],
+                repeat: 1,
+                delay: Duration::from_millis(12),
+            })
+            .add_command(XdotoolCommand::Type {
+                text: "hello world!".into(),
+                delay: Duration::from_millis(12),
+            })
+            .add_command(XdotoolCommand::Sleep(Duration::from_millis(500)))
+            .add_command(XdotoolCommand::MouseMove(100, 200))
+            .add_command(XdotoolCommand::Click {
+                button: MouseButton::Left,
+                repeat: 2,
+                delay: Duration::from_millis(100),
+            })
+            .add_command(XdotoolCommand::KeyDown(vec![super_key]))
+            .add_command(XdotoolCommand::Key {
+                keys_clicks: vec![shift_a],
+                repeat: 1,
+                delay: Duration::from_millis(50),
+            })
+            .add_command(XdotoolCommand::KeyUp(vec![super_key]))
+            .add_command(XdotoolCommand::MouseDown(MouseButton::Right))
+            .add_command(XdotoolCommand::MouseUp(MouseButton::Right));
+
+        assert_eq!(script, expected);
+    }
+
+    #[test]
+    fn test_xdotool_parse_type_takes_the_line() {
+        let script = XdotoolScript::parse("type please click here\ntype I key in").unwrap();
+
+        assert_eq!(
+            script.commands(),
+            &[
+                XdotoolCommand::Type {
+                    text: "pleaseclickhere".into(),
+                    delay: Duration::from_millis(12),
+                },
+                XdotoolCommand::Type {
+                    text: "Ikeyin".into(),
+                    delay: Duration::from_millis(12),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xdotool_parse_quotes() {
+        let script = XdotoolScript::parse("type \"say \\\"hi\\\"\" 'key'\\\n  'a\nb'").unwrap();
+
+        assert_eq!(
+            script.commands(),
+            &[XdotoolCommand::Type {
+                text: "say \"hi\"keya\nb".into(),
+                delay: Duration::from_millis(12),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_xdotool_parse_errors() {
+        assert_eq!(
+            error("key ctrl+Retrun"),
+            (ParseKeyErrorReason::UnknownKey, "Retrun".into(), 9..15)
+        );
+        assert_eq!(
+            XdotoolScript::parse("key Retrun").unwrap_err().suggestion(),
+            Some("Return")
+        );
+        assert_eq!(
+            error("key a\nwindowactivate 1"),
+            (
+                ParseKeyErrorReason::UnsupportedCommand,
+                "windowactivate".into(),
+                6..20
+            )
+        );
+        assert_eq!(
+            error("key --clearmodifiers a"),
+            (
+                ParseKeyErrorReason::UnsupportedCommand,
+                "--clearmodifiers".into(),
+                4..20
+            )
+        );
+        assert_eq!(
+            error("key"),
+            (ParseKeyErrorReason::MissingKeys, "key".into(), 0..3)
+        );
+        assert_eq!(
+            error("mousemove 100 x"),
+            (ParseKeyErrorReason::InvalidArgument, "x".into(), 14..15)
+        );
+        assert_eq!(
+            error("mousemove 100"),
+            (
+                ParseKeyErrorReason::InvalidArgument,
+                "mousemove".into(),
+                0..9
+            )
+        );
+        assert_eq!(
+            error("click 4"),
+            (ParseKeyErrorReason::UnsupportedCommand, "4".into(), 6..7)
+        );
+        assert_eq!(
+            error("sleep soon"),
+            (ParseKeyErrorReason::InvalidDuration, "soon".into(), 6..10)
+        );
+        assert_eq!(
+            error("sleep 1e20"),
+            (ParseKeyErrorReason::InvalidDuration, "1e20".into(), 6..10)
+        );
+        assert_eq!(
+            error("sleep"),
+            (ParseKeyErrorReason::MissingDuration, "sleep".into(), 0..5)
+        );
+        assert_eq!(
+            error("type 'abc"),
+            (
+                ParseKeyErrorReason::MissingClosingQuote,
+                "'abc".into(),
+                5..9
+            )
+        );
+    }
+
+    #[test]
+    fn test_xdotool_run() {
+        let keyboard_transport = RecordingTransport::default();
+        let mouse_transport = RecordingTransport::default();
+        let clock = RecordingClock::default();
+
+        let keyboard = Keyboard::from_transport(keyboard_transport.clone())
+            .set_clock(clock.clone())
+            .set_lock_state_provider(FakeLockState::default());
+        let mouse = mouse(mouse_transport.clone());
+
+        let script = XdotoolScript::parse(
+            "key --repeat 2 ctrl+v type --delay 0 ab\n\
+             sleep 1.5 mousemove 100 200 click 1 mousedown 3 mousemove 0 0 mouseup 3",
+        )
+        .unwrap();
+
+        script
+            .run(&keyboard, &mouse, &KeyboardLayout::us())
+            .unwrap();
+
+        let keys: Vec<(u8, u8)> = keyboard_transport
+            .reports()
+            .iter()
+            .map(|r| (r[3], r[5]))
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                (1, 0x19),
+                (0, 0),
+                (1, 0x19),
+                (0, 0),
+                (0, 0x04),
+                (0, 0),
+                (0, 0x05),
+                (0, 0),
+            ]
+        );
+        assert!(clock.sleeps().contains(&Duration::from_millis(12)));
+        assert!(clock.sleeps().contains(&Duration::from_millis(1500)));
+
+        // Buttons and coordinates, scaled to the 0 to 32767 range of the reports.
+        let clicks: Vec<(u8, u16, u16)> = mouse_transport
+            .reports()
+            .iter()
+            .map(|r| {
+                (
+                    r[3],
+                    u16::from_le_bytes([r[4], r[5]]),
+                    u16::from_le_bytes([r[6], r[7]]),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            clicks,
+            vec![
+                (0, 1706, 6068),
+                (1, 1706, 6068),
+                (0, 1706, 6068),
+                (2, 1706, 6068),
+                (2, 0, 0),
+                (0, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xdotool_error_display() {
+        assert_eq!(
+            XdotoolError::from(KeyboardError::UnmappedCharacter('\u{263A}')).to_string(),
+            "character '\u{263A}' is not on the keyboard layout"
+        );
+        assert_eq!(
+            XdotoolError::Keyboard(KeyboardError::TooManyKeys(8)).to_string(),
+            "8 keys do not fit in a single report"
+        );
+        assert_eq!(
+            XdotoolError::MouseSendFailed.to_string(),
+            "failed to send the mouse report"
+        );
+    }
+}